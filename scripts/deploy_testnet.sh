@@ -16,7 +16,7 @@ echo "\nConnected to wallet '$KEY_NAME'<$KEY_TYPE> @ $KEY_ADDRESS";
 echo "\n========\n";
 
 # Instantiate message config
-export INSTANTIATE_MSG="{\"max_currencies\": 3, \"amounts\": [\"100000000\", \"250000000\", \"500000000\"], \"expiries\": [600, 900, 1800], \"fee_bps\": 400, \"fairburn_bps\": 100,  \"fee_address\": \"$KEY_ADDRESS\", \"collection_address\": \"stars1xsag8jwxfp2crgulytm9kew9fjxafft9rxu6pz2m4w7eu74rcmjs88mmcy\", \"matchmaking_expiry\": 900}";
+export INSTANTIATE_MSG="{\"max_currencies\": 3, \"amounts\": [\"100000000\", \"250000000\", \"500000000\"], \"expiries\": [600, 900, 1800], \"twap_windows\": [300], \"fee_bps\": 400, \"fairburn_bps\": 100,  \"fee_address\": \"$KEY_ADDRESS\", \"collection_address\": \"stars1xsag8jwxfp2crgulytm9kew9fjxafft9rxu6pz2m4w7eu74rcmjs88mmcy\", \"matchmaking_expiry\": 900, \"oracles\": [], \"min_price_sources\": 1, \"max_price_age\": 300, \"signers\": [], \"signature_threshold\": 1, \"attestation_window\": 10, \"keeper_reward_bps\": 1000, \"prune_reward_bps\": 0, \"settlement_grace_period\": 86400, \"dispute_window\": 0, \"dispute_bond\": \"0\", \"payout_policy\": \"settlement_owner\", \"token_wager_fee\": \"10000000\", \"currencies\": [\"dot\", \"avax\", \"uni\", \"atom\", \"link\", \"near\", \"icp\", \"sand\", \"btc\", \"eth\", \"bnb\", \"xrp\", \"ada\", \"doge\", \"sol\", \"mana\", \"cake\", \"ar\", \"osmo\", \"rune\", \"luna\", \"ustc\", \"stars\", \"mir\"], \"cross_collection\": false}";
 # echo $INSTANTIATE_MSG;
 
 ## INIT ##
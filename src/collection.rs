@@ -0,0 +1,119 @@
+use cosmwasm_std::{Addr, Decimal, Deps, DepsMut, Order, StdResult, Storage, Uint128};
+use cw20::Denom;
+use sg_std::Response;
+
+use crate::error::ContractError;
+use crate::msg::{CollectionResponse, CollectionsResponse};
+use crate::state::{CollectionConfig, Config, WagerKey, COLLECTIONS};
+
+// Load a collection from the registry
+pub fn load_collection(
+    storage: &dyn Storage,
+    address: &Addr,
+) -> Result<CollectionConfig, ContractError> {
+    COLLECTIONS
+        .may_load(storage, address)?
+        .ok_or_else(|| ContractError::UnknownCollection {
+            collection: address.to_string(),
+        })
+}
+
+// Fee of a wager, the highest fee of the collections of both tokens
+pub fn wager_fee_percent(
+    storage: &dyn Storage,
+    config: &Config,
+    id: &WagerKey,
+) -> StdResult<Decimal> {
+    // Collections removed since the wager was matched fall back to the config
+    let fee_percent = |address: &Addr| -> StdResult<Decimal> {
+        Ok(COLLECTIONS
+            .may_load(storage, address)?
+            .and_then(|collection| collection.fee_percent)
+            .unwrap_or(config.fee_percent))
+    };
+
+    Ok(fee_percent(&id.0 .0)?.max(fee_percent(&id.1 .0)?))
+}
+
+pub fn execute_set_collection(
+    deps: DepsMut,
+    address: String,
+    amounts: Option<Vec<(Denom, Vec<Uint128>)>>,
+    expiries: Option<Vec<u64>>,
+    fee_bps: Option<u64>,
+    matchmaking_expiry: Option<u64>,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+
+    if let Some(amounts) = &amounts {
+        if amounts.is_empty()
+            || amounts
+                .iter()
+                .enumerate()
+                .any(|(i, (denom, denom_amounts))| {
+                    denom_amounts.is_empty()
+                        || denom_amounts.contains(&Uint128::zero())
+                        || amounts[..i].iter().any(|(d, _)| d == denom)
+                })
+        {
+            return Err(ContractError::InvalidParameter {
+                param: "amounts".into(),
+            });
+        }
+    }
+
+    if let Some(expiries) = &expiries {
+        if expiries.is_empty() {
+            return Err(ContractError::InvalidParameter {
+                param: "expiries".into(),
+            });
+        }
+    }
+
+    if let Some(matchmaking_expiry) = matchmaking_expiry {
+        if matchmaking_expiry < 60 {
+            return Err(ContractError::InvalidParameter {
+                param: "matchmaking_expiry".into(),
+            });
+        }
+    }
+
+    COLLECTIONS.save(
+        deps.storage,
+        &address,
+        &CollectionConfig {
+            amounts,
+            expiries,
+            fee_percent: fee_bps.map(Decimal::percent),
+            matchmaking_expiry,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_collection")
+        .add_attribute("collection", address))
+}
+
+pub fn execute_remove_collection(
+    deps: DepsMut,
+    address: String,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    load_collection(deps.storage, &address)?;
+
+    // Wagers already made with tokens of the collection can still be settled
+    COLLECTIONS.remove(deps.storage, &address);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_collection")
+        .add_attribute("collection", address))
+}
+
+pub fn query_collections(deps: Deps) -> StdResult<CollectionsResponse> {
+    let collections = COLLECTIONS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(address, config)| CollectionResponse { address, config }))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CollectionsResponse { collections })
+}
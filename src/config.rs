@@ -1,18 +1,38 @@
-use crate::{error::ContractError, state::CONFIG};
+use crate::{
+    error::ContractError,
+    msg::OracleInfo,
+    oracle::validate_oracles,
+    state::{PayoutPolicy, Signer, CONFIG},
+};
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Decimal, DepsMut, Response, Uint128};
 use sg_std::StargazeMsgWrapper;
 
 #[cw_serde]
+#[derive(Default)]
 pub struct ParamInfo {
     pub max_currencies: Option<u8>,
-    pub amounts: Option<Vec<Uint128>>,
     pub expiries: Option<Vec<u64>>,
+    pub twap_windows: Option<Vec<u64>>,
     pub fee_bps: Option<u64>,
     pub fairburn_bps: Option<u64>,
     pub fee_address: Option<String>,
-    pub collection_address: Option<String>,
     pub matchmaking_expiry: Option<u64>,
+    pub oracles: Option<Vec<OracleInfo>>,
+    pub min_price_sources: Option<u8>,
+    pub max_price_age: Option<u64>,
+    pub signers: Option<Vec<Signer>>,
+    pub signature_threshold: Option<u8>,
+    pub attestation_window: Option<u64>,
+    pub keeper_reward_bps: Option<u64>,
+    pub prune_reward_bps: Option<u64>,
+    pub settlement_grace_period: Option<u64>,
+    pub dispute_window: Option<u64>,
+    pub dispute_bond: Option<Uint128>,
+    pub arbiter: Option<String>,
+    pub payout_policy: Option<PayoutPolicy>,
+    pub token_wager_fee: Option<Uint128>,
+    pub cross_collection: Option<bool>,
 }
 
 pub fn execute_update_params(
@@ -21,13 +41,27 @@ pub fn execute_update_params(
 ) -> Result<Response<StargazeMsgWrapper>, ContractError> {
     let ParamInfo {
         max_currencies,
-        amounts,
         expiries,
+        twap_windows,
         fee_bps,
         fee_address,
-        collection_address,
         matchmaking_expiry,
         fairburn_bps,
+        oracles,
+        min_price_sources,
+        max_price_age,
+        signers,
+        signature_threshold,
+        attestation_window,
+        keeper_reward_bps,
+        prune_reward_bps,
+        settlement_grace_period,
+        dispute_window,
+        dispute_bond,
+        arbiter,
+        payout_policy,
+        token_wager_fee,
+        cross_collection,
     } = param_info;
 
     let mut params = CONFIG.load(deps.storage)?;
@@ -42,14 +76,20 @@ pub fn execute_update_params(
         params.max_currencies = max_currencies;
     }
 
-    if let Some(amounts) = amounts {
-        params.amounts = amounts;
-    }
-
     if let Some(expiries) = expiries {
         params.expiries = expiries;
     }
 
+    if let Some(twap_windows) = twap_windows {
+        if twap_windows.contains(&0) {
+            return Err(ContractError::InvalidParameter {
+                param: "twap_windows".into(),
+            });
+        }
+
+        params.twap_windows = twap_windows;
+    }
+
     params.fee_percent = fee_bps.map(Decimal::percent).unwrap_or(params.fee_percent);
 
     params.fairburn_percent = fairburn_bps
@@ -60,14 +100,98 @@ pub fn execute_update_params(
         params.fee_address = deps.api.addr_validate(&fee_address)?;
     }
 
-    if let Some(collection_address) = collection_address {
-        params.collection_address = deps.api.addr_validate(&collection_address)?;
-    }
-
     if let Some(matchmaking_expiry) = matchmaking_expiry {
         params.matchmaking_expiry = matchmaking_expiry;
     }
 
+    if let Some(keeper_reward_bps) = keeper_reward_bps {
+        if keeper_reward_bps > 10_000 {
+            return Err(ContractError::InvalidParameter {
+                param: "keeper_reward_bps".into(),
+            });
+        }
+
+        params.keeper_reward_percent = Decimal::percent(keeper_reward_bps);
+    }
+
+    if let Some(prune_reward_bps) = prune_reward_bps {
+        if prune_reward_bps > 10_000 {
+            return Err(ContractError::InvalidParameter {
+                param: "prune_reward_bps".into(),
+            });
+        }
+
+        params.prune_reward_percent = Decimal::percent(prune_reward_bps);
+    }
+
+    if let Some(settlement_grace_period) = settlement_grace_period {
+        params.settlement_grace_period = settlement_grace_period;
+    }
+
+    if let Some(dispute_window) = dispute_window {
+        params.dispute_window = dispute_window;
+    }
+
+    if let Some(dispute_bond) = dispute_bond {
+        params.dispute_bond = dispute_bond;
+    }
+
+    if let Some(arbiter) = arbiter {
+        params.arbiter = Some(deps.api.addr_validate(&arbiter)?);
+    }
+
+    if let Some(payout_policy) = payout_policy {
+        params.payout_policy = payout_policy;
+    }
+
+    if let Some(token_wager_fee) = token_wager_fee {
+        params.token_wager_fee = token_wager_fee;
+    }
+
+    if let Some(cross_collection) = cross_collection {
+        params.cross_collection = cross_collection;
+    }
+
+    if let Some(oracles) = oracles {
+        params.oracles = validate_oracles(deps.api, oracles)?;
+    }
+
+    if let Some(min_price_sources) = min_price_sources {
+        params.min_price_sources = min_price_sources;
+    }
+
+    if let Some(max_price_age) = max_price_age {
+        params.max_price_age = max_price_age;
+    }
+
+    if let Some(signers) = signers {
+        params.signers = signers;
+    }
+
+    if let Some(signature_threshold) = signature_threshold {
+        params.signature_threshold = signature_threshold;
+    }
+
+    if let Some(attestation_window) = attestation_window {
+        params.attestation_window = attestation_window;
+    }
+
+    if params.signature_threshold < 1
+        || params.signature_threshold as usize > params.signers.len().max(1)
+    {
+        return Err(ContractError::InvalidParameter {
+            param: "signature_threshold".into(),
+        });
+    }
+
+    if params.min_price_sources < 1
+        || params.min_price_sources as usize > params.oracles.len().max(1)
+    {
+        return Err(ContractError::InvalidParameter {
+            param: "min_price_sources".into(),
+        });
+    }
+
     CONFIG.save(deps.storage, &params)?;
 
     Ok(Response::new().add_attribute("action", "update_params"))
@@ -1,29 +1,50 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order, StdError,
-    StdResult,
+    to_binary, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order, StdError, StdResult,
 };
 use cw2::set_contract_version;
+use cw20::Denom;
+use cw_storage_plus::Bound;
 use semver::Version;
-use sg_std::Response;
+use sg_std::{Response, NATIVE_DENOM};
 
+use crate::collection::{execute_remove_collection, execute_set_collection, query_collections};
 use crate::config::execute_update_params;
+use crate::currency::{
+    execute_add_currency, execute_disable_currency, execute_set_currency_feed,
+    execute_set_currency_volatility, query_currencies, validate_currency_id,
+};
+use crate::denom::{execute_remove_denom, execute_set_denom, query_denoms};
 use crate::error::ContractError;
-use crate::execute::{execute_cancel, execute_set_winner, execute_wager};
+use crate::execute::{
+    execute_accept_challenge, execute_cancel, execute_challenge, execute_claim_refund,
+    execute_deposit, execute_dispute_result, execute_finalize_result, execute_prune_matchmaking,
+    execute_receive, execute_receive_nft, execute_record_prices, execute_resolve_dispute,
+    execute_set_winner, execute_set_winners, execute_take_over, execute_update_prices,
+    execute_wager, execute_withdraw,
+};
 use crate::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MatchmakingResponse, QueryMsg, TokenStatusResponse,
-    WagerResponse, WagersResponse,
+    ChallengesResponse, ConfigResponse, DepositResponse, ExecuteMsg, InstantiateMsg,
+    MatchmakingResponse, ProposedResultResponse, QueryMsg, TokenStatusResponse, WagerResponse,
+    WagersResponse,
 };
+use crate::oracle::validate_oracles;
 use crate::state::{
-    wagers, Config, MatchmakingItem, MatchmakingItemExport, Token, TokenStatus, Wager, WagerExport,
-    WagerInfo, CONFIG, MATCHMAKING, NFT,
+    challenges, denom_key, matchmaking, wagers, Challenge, ChallengeExport, CollectionConfig,
+    Config, CurrencyInfo, Direction, MatchmakingItem, MatchmakingItemExport, PayoutPolicy,
+    ScoringMode, SettlementMode, Token, TokenStatus, Wager, WagerExport, WagerInfo, WagerType,
+    COLLECTIONS, CONFIG, CURRENCIES, DENOMS, DEPOSITS, NFT, PROPOSED_RESULTS,
 };
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw-wager";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// pagination info for queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -37,11 +58,28 @@ pub fn instantiate(
         max_currencies,
         amounts,
         expiries,
+        twap_windows,
         fee_bps,
         fee_address,
         collection_address,
         matchmaking_expiry,
         fairburn_bps,
+        oracles,
+        min_price_sources,
+        max_price_age,
+        signers,
+        signature_threshold,
+        attestation_window,
+        keeper_reward_bps,
+        prune_reward_bps,
+        settlement_grace_period,
+        dispute_window,
+        dispute_bond,
+        arbiter,
+        payout_policy,
+        token_wager_fee,
+        currencies,
+        cross_collection,
     } = msg;
 
     if max_currencies < 1 {
@@ -62,6 +100,30 @@ pub fn instantiate(
         });
     }
 
+    if twap_windows.contains(&0) {
+        return Err(ContractError::InvalidParameter {
+            param: "twap_windows".into(),
+        });
+    }
+
+    if signature_threshold < 1 || signature_threshold as usize > signers.len().max(1) {
+        return Err(ContractError::InvalidParameter {
+            param: "signature_threshold".into(),
+        });
+    }
+
+    if keeper_reward_bps > 10_000 {
+        return Err(ContractError::InvalidParameter {
+            param: "keeper_reward_bps".into(),
+        });
+    }
+
+    if prune_reward_bps > 10_000 {
+        return Err(ContractError::InvalidParameter {
+            param: "prune_reward_bps".into(),
+        });
+    }
+
     if matchmaking_expiry < 60 {
         return Err(ContractError::InvalidParameter {
             param: "matchmaking_expiry".into(),
@@ -70,18 +132,69 @@ pub fn instantiate(
 
     let fee_address = deps.api.addr_validate(&fee_address)?;
     let collection_address = deps.api.addr_validate(&collection_address)?;
+    let arbiter = arbiter
+        .map(|arbiter| deps.api.addr_validate(&arbiter))
+        .transpose()?;
+    let oracles = validate_oracles(deps.api, oracles)?;
+
+    if min_price_sources < 1 || min_price_sources as usize > oracles.len().max(1) {
+        return Err(ContractError::InvalidParameter {
+            param: "min_price_sources".into(),
+        });
+    }
+
+    for id in currencies {
+        validate_currency_id(&id)?;
+        CURRENCIES.save(
+            deps.storage,
+            &id,
+            &CurrencyInfo {
+                enabled: true,
+                feed_id: id.clone(),
+                volatility: None,
+            },
+        )?;
+    }
+
+    // The first collection uses the options of the config
+    COLLECTIONS.save(
+        deps.storage,
+        &collection_address,
+        &CollectionConfig::default(),
+    )?;
+
+    // The amount options of the instantiate message apply to the native denom
+    DENOMS.save(
+        deps.storage,
+        &denom_key(&Denom::Native(NATIVE_DENOM.to_string())),
+        &amounts,
+    )?;
 
     CONFIG.save(
         deps.storage,
         &Config {
             max_currencies,
-            amounts,
             expiries,
+            twap_windows,
             fee_percent: Decimal::percent(fee_bps),
             fairburn_percent: Decimal::percent(fairburn_bps),
             fee_address,
-            collection_address,
+            cross_collection,
             matchmaking_expiry,
+            oracles,
+            min_price_sources,
+            max_price_age,
+            signers,
+            signature_threshold,
+            attestation_window,
+            keeper_reward_percent: Decimal::percent(keeper_reward_bps),
+            prune_reward_percent: Decimal::percent(prune_reward_bps),
+            settlement_grace_period,
+            dispute_window,
+            dispute_bond,
+            arbiter,
+            payout_policy: payout_policy.unwrap_or(PayoutPolicy::SettlementOwner),
+            token_wager_fee,
         },
     )?;
 
@@ -102,21 +215,130 @@ pub fn execute(
             admin_only(deps.as_ref(), info)?;
             execute_update_params(deps, params)
         }
+        ExecuteMsg::AddCurrency { id, feed_id } => {
+            admin_only(deps.as_ref(), info)?;
+            execute_add_currency(deps, id, feed_id)
+        }
+        ExecuteMsg::DisableCurrency { id } => {
+            admin_only(deps.as_ref(), info)?;
+            execute_disable_currency(deps, id)
+        }
+        ExecuteMsg::SetCurrencyFeed { id, feed_id } => {
+            admin_only(deps.as_ref(), info)?;
+            execute_set_currency_feed(deps, id, feed_id)
+        }
+        ExecuteMsg::SetCurrencyVolatility { id, volatility } => {
+            admin_or_oracle(deps.as_ref(), info)?;
+            execute_set_currency_volatility(deps, id, volatility)
+        }
+        ExecuteMsg::SetDenom { denom, amounts } => {
+            admin_only(deps.as_ref(), info)?;
+            execute_set_denom(deps, denom, amounts)
+        }
+        ExecuteMsg::RemoveDenom { denom } => {
+            admin_only(deps.as_ref(), info)?;
+            execute_remove_denom(deps, denom)
+        }
+        ExecuteMsg::SetCollection {
+            address,
+            amounts,
+            expiries,
+            fee_bps,
+            matchmaking_expiry,
+        } => {
+            admin_only(deps.as_ref(), info)?;
+            execute_set_collection(
+                deps,
+                address,
+                amounts,
+                expiries,
+                fee_bps,
+                matchmaking_expiry,
+            )
+        }
+        ExecuteMsg::RemoveCollection { address } => {
+            admin_only(deps.as_ref(), info)?;
+            execute_remove_collection(deps, address)
+        }
+        ExecuteMsg::UpdatePrices { prices } => {
+            admin_only(deps.as_ref(), info)?;
+            execute_update_prices(deps, env, prices)
+        }
+        ExecuteMsg::RecordPrices { currencies } => execute_record_prices(deps, currencies),
+        ExecuteMsg::ResolveDispute { wager_key, winner } => {
+            arbiter_only(deps.as_ref(), info)?;
+            execute_resolve_dispute(deps, wager_key, winner)
+        }
         ExecuteMsg::SetWinner {
             wager_key,
-            prev_prices,
-            current_prices,
+            attestations,
         } => {
-            admin_only(deps.as_ref(), info)?;
-            execute_set_winner(deps, env, wager_key, prev_prices, current_prices)
+            // Anyone can settle once prices are read from oracles or signed by trusted keys
+            if CONFIG.load(deps.storage)?.oracles.is_empty() && attestations.is_none() {
+                admin_only(deps.as_ref(), info.clone())?;
+            }
+            execute_set_winner(deps, env, info, wager_key, attestations)
         }
+        ExecuteMsg::SetWinners { results } => {
+            if CONFIG.load(deps.storage)?.oracles.is_empty()
+                && results.iter().any(|r| r.attestations.is_none())
+            {
+                admin_only(deps.as_ref(), info.clone())?;
+            }
+            execute_set_winners(deps, env, info, results)
+        }
+        ExecuteMsg::FinalizeResult { wager_key } => execute_finalize_result(deps, env, wager_key),
         ExecuteMsg::Wager {
             token,
             currency,
             against_currencies,
             expiry,
-        } => execute_wager(deps, env, info, token, currency, against_currencies, expiry),
+            settlement_mode,
+            direction,
+            wager_type,
+            against_amount,
+            scoring_mode,
+        } => execute_wager(
+            deps,
+            env,
+            info,
+            token,
+            currency,
+            against_currencies,
+            expiry,
+            settlement_mode.unwrap_or(SettlementMode::Spot),
+            direction.unwrap_or(Direction::Long),
+            wager_type.unwrap_or(WagerType::Race),
+            against_amount,
+            scoring_mode.unwrap_or(ScoringMode::Raw),
+        ),
+        ExecuteMsg::ReceiveNft(msg) => execute_receive_nft(deps, env, info, msg),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::Deposit {} => execute_deposit(deps, info),
+        ExecuteMsg::Withdraw {} => execute_withdraw(deps, info),
         ExecuteMsg::Cancel { token } => execute_cancel(deps, info, token),
+        ExecuteMsg::TakeOver { token } => execute_take_over(deps, info, token),
+        ExecuteMsg::Challenge {
+            token,
+            opponent_token,
+            currency,
+            against_currency,
+            amount,
+            expiry,
+        } => execute_challenge(
+            deps,
+            info,
+            token,
+            opponent_token,
+            currency,
+            against_currency,
+            amount,
+            expiry,
+        ),
+        ExecuteMsg::AcceptChallenge { token } => execute_accept_challenge(deps, env, info, token),
+        ExecuteMsg::ClaimRefund { token } => execute_claim_refund(deps, env, info, token),
+        ExecuteMsg::PruneMatchmaking { limit } => execute_prune_matchmaking(deps, env, info, limit),
+        ExecuteMsg::DisputeResult { token } => execute_dispute_result(deps, env, info, token),
     }
 }
 
@@ -136,6 +358,14 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, Contra
         return Err(StdError::generic_err("Cannot upgrade from a newer version").into());
     }
 
+    // 0.1 kept a single collection and keyed its state by token id, which this version cannot
+    // read, so a new contract has to be instantiated instead
+    if version < Version::new(0, 2, 0) {
+        return Err(
+            StdError::generic_err("Cannot upgrade from 0.1, instantiate a new contract").into(),
+        );
+    }
+
     // set the new version
     cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
@@ -151,39 +381,36 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::Wager { token } => to_binary(&query_wager(deps, token)?),
         QueryMsg::Matchmaking {} => to_binary(&query_matchmaking(deps)?),
         QueryMsg::TokenStatus { token } => to_binary(&query_token_status(deps, token)?),
+        QueryMsg::Challenges {
+            opponent_token,
+            start_after,
+            limit,
+        } => to_binary(&query_challenges(deps, opponent_token, start_after, limit)?),
+        QueryMsg::ProposedResult { wager_key } => {
+            to_binary(&query_proposed_result(deps, wager_key)?)
+        }
+        QueryMsg::Deposit { address } => to_binary(&query_deposit(deps, address)?),
+        QueryMsg::Currencies {} => to_binary(&query_currencies(deps)?),
+        QueryMsg::Denoms {} => to_binary(&query_denoms(deps)?),
+        QueryMsg::Collections {} => to_binary(&query_collections(deps)?),
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
     }
 }
 
 pub fn query_wagers(deps: Deps) -> StdResult<WagersResponse> {
-    let config = CONFIG.load(deps.storage)?;
-
     let wagers = wagers()
         .idx
         .id
         .range(deps.storage, None, None, Order::Ascending)
-        .map(|v| export_wager(v.unwrap().1, config.collection_address.clone()))
+        .map(|v| export_wager(v.unwrap().1))
         .collect::<Vec<_>>();
 
     Ok(WagersResponse { wagers })
 }
 
 pub fn query_wager(deps: Deps, token: Token) -> StdResult<WagerResponse> {
-    let config = CONFIG.load(deps.storage)?;
-
     // Find the wager with the key containing the token and return it as WagerExport
-    let wager = wagers()
-        .idx
-        .id
-        .range(deps.storage, None, None, Order::Ascending)
-        .find(|item| {
-            item.as_ref()
-                .map(|(_, w)| w.id.0 == token || w.id.1 == token)
-                .unwrap_or(false)
-        })
-        .map(|v| export_wager(v.unwrap().1, config.collection_address));
-
-    match wager {
+    match find_wager(deps, &token)?.map(export_wager) {
         Some(wager) => Ok(WagerResponse { wager }),
         None => Err(cosmwasm_std::StdError::NotFound {
             kind: "wager".into(),
@@ -191,14 +418,30 @@ pub fn query_wager(deps: Deps, token: Token) -> StdResult<WagerResponse> {
     }
 }
 
-pub fn query_matchmaking(deps: Deps) -> StdResult<MatchmakingResponse> {
-    let config = CONFIG.load(deps.storage)?;
+// Find the wager a token takes part in, from the first token of the wager keys or the index
+// of their second token. Keys are read raw, wager keys are nested tuples that cannot be
+// deserialized
+fn find_wager(deps: Deps, token: &Token) -> StdResult<Option<Wager>> {
+    wagers()
+        .prefix(token.clone())
+        .range_raw(deps.storage, None, None, Order::Ascending)
+        .chain(wagers().idx.token.prefix(token.clone()).range_raw(
+            deps.storage,
+            None,
+            None,
+            Order::Ascending,
+        ))
+        .map(|item| item.map(|(_, wager)| wager))
+        .next()
+        .transpose()
+}
 
-    let matchmaking = MATCHMAKING
+pub fn query_matchmaking(deps: Deps) -> StdResult<MatchmakingResponse> {
+    let matchmaking = matchmaking()
         .range(deps.storage, None, None, Order::Ascending)
         .collect::<StdResult<Vec<_>>>()?
-        .iter()
-        .map(|v| export_matchmaking(v.clone().0, v.clone().1, config.collection_address.clone()))
+        .into_iter()
+        .map(|(token, v)| export_matchmaking(token, v))
         .collect();
 
     Ok(MatchmakingResponse { matchmaking })
@@ -209,52 +452,25 @@ pub fn query_token_status(deps: Deps, token: Token) -> StdResult<TokenStatusResp
     // If there is a MatchmakingItem for the token, return TokenStatus::Matchmaking(MatchmakingItem).
     // If there is no Wager or MatchmakingItem for the token, return TokenStatus::None.
 
-    let config = CONFIG.load(deps.storage)?;
-
-    let wager = wagers()
-        .idx
-        .id
-        .range(deps.storage, None, None, Order::Ascending)
-        .find(|item| {
-            item.as_ref()
-                .map(|(_, w)| w.id.0 == token || w.id.1 == token)
-                .unwrap_or(false)
-        })
-        .map(|v| export_wager(v.unwrap().1, config.collection_address.clone()));
-
-    if let Some(wager) = wager {
+    if let Some(wager) = find_wager(deps, &token)?.map(export_wager) {
         return Ok(TokenStatusResponse {
             token_status: TokenStatus::Wager(wager),
         });
     }
 
-    let matchmaking_item =
-        MATCHMAKING
-            .may_load(deps.storage, token)?
-            .ok_or(cosmwasm_std::StdError::NotFound {
-                kind: "matchmaking_item".into(),
-            });
+    let matchmaking_item = matchmaking().may_load(deps.storage, token.clone())?.ok_or(
+        cosmwasm_std::StdError::NotFound {
+            kind: "matchmaking_item".into(),
+        },
+    );
 
     if matchmaking_item.is_ok() {
-        let MatchmakingItem {
-            expires_at,
-            currency,
-            against_currencies,
-            expiry,
-            amount,
-        } = matchmaking_item?;
         Ok(TokenStatusResponse {
-            token_status: TokenStatus::Matchmaking(MatchmakingItemExport {
-                token: NFT {
-                    collection: config.collection_address,
-                    token_id: token,
-                },
-                expires_at,
-                currency,
-                against_currencies,
-                expiry,
-                amount,
-            }),
+            token_status: TokenStatus::Matchmaking(export_matchmaking(token, matchmaking_item?)),
+        })
+    } else if let Some(challenge) = challenges().may_load(deps.storage, token.clone())? {
+        Ok(TokenStatusResponse {
+            token_status: TokenStatus::Challenge(export_challenge(token, challenge)),
         })
     } else {
         Ok(TokenStatusResponse {
@@ -263,49 +479,146 @@ pub fn query_token_status(deps: Deps, token: Token) -> StdResult<TokenStatusResp
     }
 }
 
+pub fn query_challenges(
+    deps: Deps,
+    opponent_token: Option<Token>,
+    start_after: Option<Token>,
+    limit: Option<u32>,
+) -> StdResult<ChallengesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let challenges = match opponent_token {
+        Some(opponent_token) => challenges()
+            .idx
+            .opponent
+            .prefix(opponent_token)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+        None => challenges()
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+    }
+    .into_iter()
+    .map(|(token, v)| export_challenge(token, v))
+    .collect();
+
+    Ok(ChallengesResponse { challenges })
+}
+
+pub fn query_proposed_result(
+    deps: Deps,
+    wager_key: (Token, Token),
+) -> StdResult<ProposedResultResponse> {
+    let result = PROPOSED_RESULTS
+        .load(deps.storage, wager_key.clone())
+        .or_else(|_| {
+            PROPOSED_RESULTS.load(deps.storage, (wager_key.1.clone(), wager_key.0.clone()))
+        })?;
+
+    Ok(ProposedResultResponse { result })
+}
+
+fn query_deposit(deps: Deps, address: String) -> StdResult<DepositResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let amount = DEPOSITS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(DepositResponse { amount })
+}
+
 fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
     Ok(ConfigResponse { config })
 }
 
-fn export_wager(v: Wager, collection: Addr) -> WagerExport {
+// Collection address and token id of a token
+fn export_token((collection, token_id): Token) -> NFT {
+    NFT {
+        collection,
+        token_id,
+    }
+}
+
+fn export_wager(v: Wager) -> WagerExport {
     WagerExport {
-        amount: v.amount,
+        denom: v.denom,
         expires_at: v.expires_at,
+        settlement_mode: v.settlement_mode,
+        stake_type: v.stake_type,
+        wager_type: v.wager_type,
+        scoring_mode: v.scoring_mode,
         wagers: (
             WagerInfo {
-                token: NFT {
-                    collection: collection.clone(),
-                    token_id: v.id.0,
-                },
-                currency: v.currencies.0,
+                token: export_token(v.id.0),
+                basket: v.baskets.0,
+                opening_prices: v.opening_prices.0,
+                participant: v.participants.0,
+                direction: v.directions.0,
+                stake: v.stakes.0,
             },
             WagerInfo {
-                token: NFT {
-                    collection,
-                    token_id: v.id.1,
-                },
-                currency: v.currencies.1,
+                token: export_token(v.id.1),
+                basket: v.baskets.1,
+                opening_prices: v.opening_prices.1,
+                participant: v.participants.1,
+                direction: v.directions.1,
+                stake: v.stakes.1,
             },
         ),
     }
 }
 
-fn export_matchmaking(
-    token_id: u64,
-    v: MatchmakingItem,
-    collection: Addr,
-) -> MatchmakingItemExport {
+fn export_matchmaking(token: Token, v: MatchmakingItem) -> MatchmakingItemExport {
     MatchmakingItemExport {
-        token: NFT {
-            collection,
-            token_id,
-        },
-        currency: v.currency,
+        token: export_token(token),
+        basket: v.basket,
         against_currencies: v.against_currencies,
         expires_at: v.expires_at,
         expiry: v.expiry,
+        denom: v.denom,
         amount: v.amount,
+        against_amount: v.against_amount,
+        settlement_mode: v.settlement_mode,
+        direction: v.direction,
+        wager_type: v.wager_type,
+        scoring_mode: v.scoring_mode,
+        depositor: v.depositor,
+        stake_type: v.stake_type,
+    }
+}
+
+fn export_challenge(token: Token, v: Challenge) -> ChallengeExport {
+    ChallengeExport {
+        token: export_token(token),
+        opponent_token: export_token(v.opponent_token),
+        currency: v.currency,
+        against_currency: v.against_currency,
+        denom: v.denom,
+        amount: v.amount,
+        expiry: v.expiry,
+        depositor: v.depositor,
+    }
+}
+
+fn arbiter_only(deps: Deps, info: MessageInfo) -> Result<Empty, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.fee_address && Some(info.sender) != config.arbiter {
+        Err(ContractError::Unauthorized {})
+    } else {
+        Ok(Empty {})
+    }
+}
+
+fn admin_or_oracle(deps: Deps, info: MessageInfo) -> Result<Empty, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.fee_address && !config.oracles.iter().any(|o| o.address == info.sender)
+    {
+        Err(ContractError::Unauthorized {})
+    } else {
+        Ok(Empty {})
     }
 }
 
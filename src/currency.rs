@@ -0,0 +1,206 @@
+use cosmwasm_std::{Decimal, Deps, DepsMut, Order, StdResult, Storage};
+use sg_std::Response;
+
+use crate::error::ContractError;
+use crate::msg::{CurrenciesResponse, CurrencyResponse};
+use crate::state::{Basket, CurrencyId, CurrencyInfo, CURRENCIES};
+
+// Volatility factors of the currencies of a basket
+pub fn load_volatilities(
+    storage: &dyn Storage,
+    basket: &Basket,
+) -> Result<Vec<Decimal>, ContractError> {
+    basket
+        .iter()
+        .map(|(currency, _)| {
+            load_currency(storage, currency)?.volatility.ok_or_else(|| {
+                ContractError::VolatilityNotSet {
+                    currency: currency.to_string(),
+                }
+            })
+        })
+        .collect()
+}
+
+// Verify that a currency id is a non-empty lowercase alphanumeric string
+pub fn validate_currency_id(id: &str) -> Result<(), ContractError> {
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    {
+        return Err(ContractError::InvalidParameter {
+            param: "currency".into(),
+        });
+    }
+
+    Ok(())
+}
+
+// Verify that a basket holds up to `max_currencies` distinct enabled currencies, with
+// nonzero weights that add up to one
+pub fn assert_basket(
+    storage: &dyn Storage,
+    basket: &Basket,
+    max_currencies: u8,
+) -> Result<(), ContractError> {
+    let invalid = || ContractError::InvalidParameter {
+        param: "currency".into(),
+    };
+
+    if basket.is_empty() || basket.len() > max_currencies as usize {
+        return Err(invalid());
+    }
+
+    let mut total = Decimal::zero();
+    for (i, (currency, weight)) in basket.iter().enumerate() {
+        if weight.is_zero() || basket[..i].iter().any(|(c, _)| c == currency) {
+            return Err(invalid());
+        }
+        total += *weight;
+
+        assert_enabled(storage, currency)?;
+    }
+
+    if total != Decimal::one() {
+        return Err(invalid());
+    }
+
+    Ok(())
+}
+
+// Load a currency from the registry
+pub fn load_currency(storage: &dyn Storage, id: &str) -> Result<CurrencyInfo, ContractError> {
+    CURRENCIES
+        .may_load(storage, id)?
+        .ok_or_else(|| ContractError::UnknownCurrency {
+            currency: id.to_string(),
+        })
+}
+
+// Verify that new wagers can be made on a currency
+pub fn assert_enabled(storage: &dyn Storage, id: &str) -> Result<(), ContractError> {
+    if !load_currency(storage, id)?.enabled {
+        return Err(ContractError::CurrencyDisabled {
+            currency: id.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+pub fn execute_add_currency(
+    deps: DepsMut,
+    id: CurrencyId,
+    feed_id: Option<String>,
+) -> Result<Response, ContractError> {
+    validate_currency_id(&id)?;
+
+    // Re-enable the currency if it already exists, keeping its feed id unless a new one is given
+    // and its volatility factor
+    let existing = CURRENCIES.may_load(deps.storage, &id)?;
+    let volatility = existing.as_ref().and_then(|currency| currency.volatility);
+    let feed_id = match (feed_id, existing) {
+        (Some(feed_id), _) => feed_id,
+        (None, Some(currency)) => currency.feed_id,
+        (None, None) => id.clone(),
+    };
+
+    CURRENCIES.save(
+        deps.storage,
+        &id,
+        &CurrencyInfo {
+            enabled: true,
+            feed_id: feed_id.clone(),
+            volatility,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_currency")
+        .add_attribute("currency", id)
+        .add_attribute("feed_id", feed_id))
+}
+
+pub fn execute_disable_currency(deps: DepsMut, id: CurrencyId) -> Result<Response, ContractError> {
+    let currency = load_currency(deps.storage, &id)?;
+
+    CURRENCIES.save(
+        deps.storage,
+        &id,
+        &CurrencyInfo {
+            enabled: false,
+            ..currency
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "disable_currency")
+        .add_attribute("currency", id))
+}
+
+pub fn execute_set_currency_feed(
+    deps: DepsMut,
+    id: CurrencyId,
+    feed_id: String,
+) -> Result<Response, ContractError> {
+    let currency = load_currency(deps.storage, &id)?;
+
+    CURRENCIES.save(
+        deps.storage,
+        &id,
+        &CurrencyInfo {
+            feed_id: feed_id.clone(),
+            ..currency
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_currency_feed")
+        .add_attribute("currency", id)
+        .add_attribute("feed_id", feed_id))
+}
+
+pub fn execute_set_currency_volatility(
+    deps: DepsMut,
+    id: CurrencyId,
+    volatility: Decimal,
+) -> Result<Response, ContractError> {
+    let currency = load_currency(deps.storage, &id)?;
+
+    if volatility.is_zero() {
+        return Err(ContractError::InvalidParameter {
+            param: "volatility".into(),
+        });
+    }
+
+    CURRENCIES.save(
+        deps.storage,
+        &id,
+        &CurrencyInfo {
+            volatility: Some(volatility),
+            ..currency
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_currency_volatility")
+        .add_attribute("currency", id)
+        .add_attribute("volatility", volatility.to_string()))
+}
+
+pub fn query_currencies(deps: Deps) -> StdResult<CurrenciesResponse> {
+    let currencies = CURRENCIES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(id, info)| CurrencyResponse {
+                id,
+                enabled: info.enabled,
+                feed_id: info.feed_id,
+                volatility: info.volatility,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CurrenciesResponse { currencies })
+}
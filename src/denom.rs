@@ -0,0 +1,102 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Order, StdResult, Storage, Uint128};
+use cw20::Denom;
+use sg_std::Response;
+
+use crate::error::ContractError;
+use crate::msg::{DenomResponse, DenomsResponse};
+use crate::state::{denom_key, DENOMS};
+
+// Native denom or address of a cw20 token
+pub fn denom_name(denom: &Denom) -> &str {
+    match denom {
+        Denom::Native(denom) => denom,
+        Denom::Cw20(address) => address.as_str(),
+    }
+}
+
+// Verify that a stake is one of the allowed amounts of an accepted denom,
+// or of `collection_amounts` when the collection sets its own for the denom
+pub fn assert_stake(
+    storage: &dyn Storage,
+    denom: &Denom,
+    amount: Uint128,
+    collection_amounts: Option<&[Uint128]>,
+) -> Result<(), ContractError> {
+    let denom_amounts = DENOMS
+        .may_load(storage, &denom_key(denom))?
+        .ok_or_else(|| ContractError::DenomNotAccepted {
+            denom: denom_name(denom).to_string(),
+        })?;
+
+    if !collection_amounts
+        .unwrap_or(&denom_amounts)
+        .contains(&amount)
+    {
+        return Err(ContractError::InvalidParameter {
+            param: "amount".into(),
+        });
+    }
+
+    Ok(())
+}
+
+pub fn execute_set_denom(
+    deps: DepsMut,
+    denom: Denom,
+    amounts: Vec<Uint128>,
+) -> Result<Response, ContractError> {
+    let denom = match denom {
+        Denom::Native(denom) if denom.is_empty() => {
+            return Err(ContractError::InvalidParameter {
+                param: "denom".into(),
+            })
+        }
+        Denom::Native(denom) => Denom::Native(denom),
+        Denom::Cw20(address) => Denom::Cw20(deps.api.addr_validate(address.as_str())?),
+    };
+
+    if amounts.is_empty() || amounts.contains(&Uint128::zero()) {
+        return Err(ContractError::InvalidParameter {
+            param: "amounts".into(),
+        });
+    }
+
+    DENOMS.save(deps.storage, &denom_key(&denom), &amounts)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_denom")
+        .add_attribute("denom", denom_name(&denom)))
+}
+
+pub fn execute_remove_denom(deps: DepsMut, denom: Denom) -> Result<Response, ContractError> {
+    let key = denom_key(&denom);
+    if !DENOMS.has(deps.storage, &key) {
+        return Err(ContractError::DenomNotAccepted {
+            denom: denom_name(&denom).to_string(),
+        });
+    }
+
+    // Wagers already staked in the denom are still paid out in it
+    DENOMS.remove(deps.storage, &key);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_denom")
+        .add_attribute("denom", denom_name(&denom)))
+}
+
+pub fn query_denoms(deps: Deps) -> StdResult<DenomsResponse> {
+    let denoms = DENOMS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(key, amounts)| DenomResponse {
+                denom: match key.split_once(':') {
+                    Some(("cw20", address)) => Denom::Cw20(Addr::unchecked(address)),
+                    _ => Denom::Native(key.trim_start_matches("native:").to_string()),
+                },
+                amounts,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(DenomsResponse { denoms })
+}
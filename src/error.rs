@@ -22,9 +22,63 @@ pub enum ContractError {
     #[error("Wager Still Active")]
     WagerActive {},
 
+    #[error("Settlement Grace Period Active")]
+    GracePeriodActive {},
+
+    #[error("Token Not Wagered")]
+    NotWagered {},
+
+    #[error("Result Pending")]
+    ResultPending {},
+
+    #[error("Result Disputed")]
+    ResultDisputed {},
+
+    #[error("Result Not Disputed")]
+    NotDisputed {},
+
+    #[error("Dispute Window Active")]
+    DisputeWindowActive {},
+
+    #[error("Dispute Window Closed")]
+    DisputeWindowClosed {},
+
     #[error("Token Not Matchmaking")]
     NotMatchmaking {},
 
+    #[error("Insufficient Deposit")]
+    InsufficientDeposit {},
+
+    #[error("Sender Already Depositor")]
+    AlreadyDepositor {},
+
+    #[error("Token Not Challenged")]
+    NotChallenged {},
+
+    #[error("Insufficient Price Sources: {currency:?}")]
+    InsufficientPriceSources { currency: String },
+
+    #[error("Invalid Price Attestation: {currency:?}")]
+    InvalidAttestation { currency: String },
+
+    #[error("Missing Price Attestation: {currency:?}")]
+    MissingAttestation { currency: String },
+
+    #[error("Unknown Currency: {currency:?}")]
+    UnknownCurrency { currency: String },
+
+    #[error("Currency Disabled: {currency:?}")]
+    CurrencyDisabled { currency: String },
+
+    #[error("Volatility Not Set: {currency:?}")]
+    VolatilityNotSet { currency: String },
+
+    #[error("Denom Not Accepted: {denom:?}")]
+    DenomNotAccepted { denom: String },
+
+    #[error("Unknown Collection: {collection:?}")]
+    UnknownCollection { collection: String },
+
     #[error("Invalid Parameter: {param:?}")]
     InvalidParameter { param: String },
 
@@ -1,33 +1,352 @@
-use cosmwasm_std::{coin, Decimal, Order, Uint128};
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{
+    coin, from_binary, Addr, Attribute, Decimal, Deps, Order, StdResult, Storage, SubMsg,
+    Timestamp, Uint128,
+};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{DepsMut, Env, MessageInfo};
+use cw20::{Cw20ReceiveMsg, Denom};
+use cw721::Cw721ReceiveMsg;
 use cw721_base::helpers::Cw721Contract;
-use cw_utils::must_pay;
+use cw_storage_plus::Bound;
+use cw_utils::{must_pay, one_coin};
 use sg1::fair_burn;
-use sg_std::{Response, NATIVE_DENOM};
+use sg_std::{Response, StargazeMsgWrapper, NATIVE_DENOM};
 
+use crate::collection::{load_collection, wager_fee_percent};
 use crate::contract::query_token_status;
+use crate::currency::{assert_basket, assert_enabled, load_currency, load_volatilities};
+use crate::denom::{assert_stake, denom_name};
 use crate::error::ContractError;
-use crate::helpers::send_tokens;
+use crate::helpers::{send_stake, send_tokens, transfer_nft};
+use crate::msg::{Cw20HookMsg, PriceAttestation, ReceiveMsg, WagerCurrency, WagerResult};
+use crate::oracle::{load_attested_price, load_price, load_twap, query_price};
 use crate::state::{
-    wagers, Currency, MatchmakingItem, Token, TokenStatus, Wager, CONFIG, MATCHMAKING,
+    basket_bucket, challenges, denom_key, matchmaking, single_currency, wagers, Basket, Challenge,
+    CollectionConfig, Config, CurrencyId, Direction, Dispute, MatchmakingItem, PayoutPolicy, Price,
+    ProposedResult, ScoringMode, SettlementMode, StakeType, Token, TokenStatus, Wager, WagerKey,
+    WagerType, COLLECTIONS, CONFIG, DEPOSITS, ESCROWED, ORACLE_OBSERVATIONS, PRICES,
+    PRICE_OBSERVATIONS, PROPOSED_RESULTS,
 };
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_wager(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     token: Token,
-    currency: Currency,
-    against_currencies: Vec<Currency>,
+    currency: WagerCurrency,
+    against_currencies: Vec<CurrencyId>,
     expiry: u64,
+    settlement_mode: SettlementMode,
+    direction: Direction,
+    wager_type: WagerType,
+    against_amount: Option<Uint128>,
+    scoring_mode: ScoringMode,
 ) -> Result<Response, ContractError> {
-    let token_id = token;
+    let stake = one_coin(&info)?;
 
-    let amount = must_pay(&info, NATIVE_DENOM)?;
+    let config = CONFIG.load(deps.storage)?;
+    let collection = load_collection(deps.storage, &token.0)?;
+
+    // Verify that the sender is the owner of the token
+    if info.sender != token_owner(deps.as_ref(), &token)? {
+        return Err(ContractError::Unauthorized {});
+    };
 
+    let matchmaking_item = MatchmakingItem {
+        expires_at: env.block.time.plus_seconds(
+            collection
+                .matchmaking_expiry
+                .unwrap_or(config.matchmaking_expiry),
+        ),
+        basket: currency.into_basket(),
+        against_currencies,
+        expiry,
+        denom: Denom::Native(stake.denom),
+        amount: stake.amount,
+        against_amount: against_amount.unwrap_or(stake.amount),
+        settlement_mode,
+        direction,
+        wager_type,
+        scoring_mode,
+        depositor: info.sender,
+        stake_type: StakeType::Funds,
+    };
+
+    place_wager(deps, env, &config, &collection, token, matchmaking_item)
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // The stake is paid in the token that sent the message, `place_wager` verifies that it is accepted
+    let sender = deps.api.addr_validate(&msg.sender)?;
+    let denom = Denom::Cw20(info.sender);
+
+    match from_binary(&msg.msg)? {
+        Cw20HookMsg::Wager {
+            token,
+            currency,
+            against_currencies,
+            expiry,
+            settlement_mode,
+            direction,
+            wager_type,
+            against_amount,
+            scoring_mode,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            let collection = load_collection(deps.storage, &token.0)?;
+
+            // Verify that the sender is the owner of the token
+            if sender != token_owner(deps.as_ref(), &token)? {
+                return Err(ContractError::Unauthorized {});
+            };
+
+            let matchmaking_item = MatchmakingItem {
+                expires_at: env.block.time.plus_seconds(
+                    collection
+                        .matchmaking_expiry
+                        .unwrap_or(config.matchmaking_expiry),
+                ),
+                basket: currency.into_basket(),
+                against_currencies,
+                expiry,
+                denom,
+                amount: msg.amount,
+                against_amount: against_amount.unwrap_or(msg.amount),
+                settlement_mode: settlement_mode.unwrap_or(SettlementMode::Spot),
+                direction: direction.unwrap_or(Direction::Long),
+                wager_type: wager_type.unwrap_or(WagerType::Race),
+                scoring_mode: scoring_mode.unwrap_or(ScoringMode::Raw),
+                depositor: sender,
+                stake_type: StakeType::Funds,
+            };
+
+            place_wager(deps, env, &config, &collection, token, matchmaking_item)
+        }
+        Cw20HookMsg::TakeOver { token } => take_over(deps, sender, token, denom, msg.amount),
+    }
+}
+
+pub fn execute_receive_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
+    // Only tokens from registered collections can be escrowed
+    let collection = COLLECTIONS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::Unauthorized {})?;
+    let matchmaking_expiry = collection
+        .matchmaking_expiry
+        .unwrap_or(config.matchmaking_expiry);
+
+    let depositor = deps.api.addr_validate(&msg.sender)?;
+    let token_id = msg
+        .token_id
+        .parse()
+        .map_err(|_| ContractError::InvalidParameter {
+            param: "token_id".into(),
+        })?;
+    let token: Token = (info.sender, token_id);
+
+    match from_binary(&msg.msg)? {
+        ReceiveMsg::Wager {
+            currency,
+            against_currencies,
+            expiry,
+            amount,
+            settlement_mode,
+            direction,
+            wager_type,
+            against_amount,
+            scoring_mode,
+        } => {
+            // Take the stake from the deposit of the sender of the NFT
+            take_deposit(deps.storage, &depositor, amount)?;
+
+            ESCROWED.save(deps.storage, token.clone(), &depositor)?;
+
+            let matchmaking_item = MatchmakingItem {
+                expires_at: env.block.time.plus_seconds(matchmaking_expiry),
+                basket: currency.into_basket(),
+                against_currencies,
+                expiry,
+                // Deposits are always made in the native denom
+                denom: Denom::Native(NATIVE_DENOM.to_string()),
+                amount,
+                against_amount: against_amount.unwrap_or(amount),
+                settlement_mode: settlement_mode.unwrap_or(SettlementMode::Spot),
+                direction: direction.unwrap_or(Direction::Long),
+                wager_type: wager_type.unwrap_or(WagerType::Race),
+                scoring_mode: scoring_mode.unwrap_or(ScoringMode::Raw),
+                depositor,
+                stake_type: StakeType::Funds,
+            };
+
+            Ok(
+                place_wager(deps, env, &config, &collection, token, matchmaking_item)?
+                    .add_attribute("escrowed", "true"),
+            )
+        }
+        ReceiveMsg::WagerToken {
+            currency,
+            against_currencies,
+            expiry,
+            settlement_mode,
+            direction,
+            wager_type,
+            scoring_mode,
+        } => {
+            // Take the flat fee from the deposit of the sender of the NFT, it is
+            // refunded like a stake if the wager is never matched
+            take_deposit(deps.storage, &depositor, config.token_wager_fee)?;
+
+            ESCROWED.save(deps.storage, token.clone(), &depositor)?;
+
+            let matchmaking_item = MatchmakingItem {
+                expires_at: env.block.time.plus_seconds(matchmaking_expiry),
+                basket: currency.into_basket(),
+                against_currencies,
+                expiry,
+                denom: Denom::Native(NATIVE_DENOM.to_string()),
+                amount: config.token_wager_fee,
+                against_amount: config.token_wager_fee,
+                settlement_mode: settlement_mode.unwrap_or(SettlementMode::Spot),
+                direction: direction.unwrap_or(Direction::Long),
+                wager_type: wager_type.unwrap_or(WagerType::Race),
+                scoring_mode: scoring_mode.unwrap_or(ScoringMode::Raw),
+                depositor,
+                stake_type: StakeType::Token,
+            };
+
+            Ok(
+                place_wager(deps, env, &config, &collection, token, matchmaking_item)?
+                    .add_attribute("escrowed", "true"),
+            )
+        }
+    }
+}
+
+// Take `amount` from the STARS deposited by `depositor`
+fn take_deposit(
+    storage: &mut dyn Storage,
+    depositor: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let deposit = DEPOSITS.may_load(storage, depositor)?.unwrap_or_default();
+    if deposit < amount {
+        return Err(ContractError::InsufficientDeposit {});
+    }
+    DEPOSITS.save(storage, depositor, &(deposit - amount))?;
+
+    Ok(())
+}
+
+pub fn execute_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let amount = must_pay(&info, NATIVE_DENOM)?;
+
+    let deposit = DEPOSITS.update(deps.storage, &info.sender, |deposit| {
+        StdResult::Ok(deposit.unwrap_or_default() + amount)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("deposit", deposit))
+}
+
+pub fn execute_withdraw(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let deposit = DEPOSITS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if deposit.is_zero() {
+        return Err(ContractError::InsufficientDeposit {});
+    }
+
+    DEPOSITS.remove(deps.storage, &info.sender);
+
+    let msg = send_tokens(info.sender, coin(deposit.u128(), NATIVE_DENOM))?;
+
+    Ok(Response::new()
+        .add_submessage(msg)
+        .add_attribute("action", "withdraw")
+        .add_attribute("amount", deposit))
+}
+
+// Owner of a token, or its depositor while it is escrowed in the contract
+pub fn token_owner(deps: Deps, token: &Token) -> Result<Addr, ContractError> {
+    if let Some(depositor) = ESCROWED.may_load(deps.storage, token.clone())? {
+        return Ok(depositor);
+    }
+
+    let owner = Cw721Contract(token.0.clone())
+        .owner_of(&deps.querier, token.1.to_string(), true)?
+        .owner;
+
+    Ok(deps.api.addr_validate(&owner)?)
+}
+
+// Address that receives the winnings or refund of `token` in a wager, depending on the payout policy
+fn payout_address(
+    deps: Deps,
+    config: &Config,
+    wager: &Wager,
+    token: &Token,
+) -> Result<Addr, ContractError> {
+    match config.payout_policy {
+        PayoutPolicy::MatchOwner if *token == wager.id.0 => Ok(wager.participants.0.clone()),
+        PayoutPolicy::MatchOwner => Ok(wager.participants.1.clone()),
+        PayoutPolicy::SettlementOwner => token_owner(deps, token),
+    }
+}
+
+// Return a token escrowed in the contract to its depositor
+fn release_escrow(
+    storage: &mut dyn Storage,
+    token: &Token,
+) -> StdResult<Option<SubMsg<StargazeMsgWrapper>>> {
+    let depositor = match ESCROWED.may_load(storage, token.clone())? {
+        Some(depositor) => depositor,
+        None => return Ok(None),
+    };
+
+    ESCROWED.remove(storage, token.clone());
+
+    transfer_nft(token.0.clone(), depositor, token.1).map(Some)
+}
+
+// Match `matchmaking_item` with an opponent, or add it to the matchmaking pool
+fn place_wager(
+    deps: DepsMut,
+    env: Env,
+    config: &Config,
+    collection: &CollectionConfig,
+    token: Token,
+    matchmaking_item: MatchmakingItem,
+) -> Result<Response, ContractError> {
+    let MatchmakingItem {
+        ref basket,
+        ref against_currencies,
+        expiry,
+        ref denom,
+        amount,
+        against_amount,
+        ref settlement_mode,
+        ref stake_type,
+        ref wager_type,
+        ref scoring_mode,
+        ..
+    } = matchmaking_item;
+
     // Verify that the currencies do not exceed the maximum amount
     if against_currencies.len() > config.max_currencies as usize {
         return Err(ContractError::InvalidParameter {
@@ -35,228 +354,1392 @@ pub fn execute_wager(
         });
     };
 
-    // Verify that the currencies do not include the base currency
-    if against_currencies.contains(&currency) {
+    // Verify that the currencies do not include the base currency, baskets of several
+    // currencies are matched with other baskets instead
+    if match single_currency(basket) {
+        Some(currency) => against_currencies.contains(currency),
+        None => !against_currencies.is_empty(),
+    } {
         return Err(ContractError::InvalidParameter {
             param: "against_currencies".into(),
         });
     };
 
-    // Verify that the expiry is within the list of allowed expiries
-    if !config.expiries.contains(&expiry) {
-        return Err(ContractError::InvalidParameter {
-            param: "expiry".into(),
-        });
+    // Verify that over/under wagers have a strike, a single currency and no currencies to wager against
+    if let WagerType::OverUnder { strike } = wager_type {
+        if strike.is_zero() {
+            return Err(ContractError::InvalidParameter {
+                param: "wager_type".into(),
+            });
+        }
+        if single_currency(basket).is_none() {
+            return Err(ContractError::InvalidParameter {
+                param: "currency".into(),
+            });
+        }
+        if !against_currencies.is_empty() {
+            return Err(ContractError::InvalidParameter {
+                param: "against_currencies".into(),
+            });
+        }
     };
 
-    // Verify that the amount is within the list of allowed amounts
-    if !config.amounts.contains(&amount) {
+    // Verify that the basket is valid and all currencies are enabled in the registry
+    assert_basket(deps.storage, basket, config.max_currencies)?;
+    for against_currency in against_currencies {
+        assert_enabled(deps.storage, against_currency)?;
+    }
+
+    // Verify that volatility-normalized wagers can be scored, the basket of the opponent
+    // is verified when it places its own wager
+    if *scoring_mode == ScoringMode::VolatilityNormalized {
+        load_volatilities(deps.storage, basket)?;
+    }
+
+    // Verify that the expiry is within the list of allowed expiries of the collection
+    if !collection
+        .expiries
+        .as_ref()
+        .unwrap_or(&config.expiries)
+        .contains(&expiry)
+    {
         return Err(ContractError::InvalidParameter {
-            param: "amount".into(),
+            param: "expiry".into(),
         });
     };
 
-    // Verify that the sender is the owner of the token
-    let token_owner = Cw721Contract(config.collection_address)
-        .owner_of(&deps.querier, token_id.to_string(), true)?
-        .owner;
-    if info.sender != token_owner {
-        return Err(ContractError::Unauthorized {});
+    // Verify that the TWAP window is within the list of allowed windows and fits in the wager
+    if let SettlementMode::Twap { window } = settlement_mode {
+        if !config.twap_windows.contains(window) || *window > expiry {
+            return Err(ContractError::InvalidParameter {
+                param: "settlement_mode".into(),
+            });
+        }
+
+        // TWAP wagers open and close on the price observations, which must be fed for the basket
+        for (currency, _) in basket {
+            load_twap(
+                deps.as_ref(),
+                config,
+                currency,
+                env.block.time,
+                env.block.time,
+            )?;
+        }
     };
 
+    // Verify that the denom is accepted and both stakes are within the list of allowed amounts,
+    // token stakes only pay the flat fee
+    if *stake_type == StakeType::Funds {
+        assert_stake(deps.storage, denom, amount, collection.denom_amounts(denom))?;
+        assert_stake(
+            deps.storage,
+            denom,
+            against_amount,
+            collection.denom_amounts(denom),
+        )?;
+    }
+
     // Verify that the token is not already wagered or is not matchmaking
-    let token_status = query_token_status(deps.as_ref(), token)?.token_status;
+    let token_status = query_token_status(deps.as_ref(), token.clone())?.token_status;
     if token_status != TokenStatus::None {
         return Err(ContractError::AlreadyWagered {});
     };
 
-    // Search for a MatchmakingItem in MATCHMAKING that matches any of the currencies in `against_currencies`.
+    // Search for a MatchmakingItem that matches any of the currencies in `against_currencies`.
     // This MatchmakingItem must also match the expiry and amount.
     // If a MatchmakingItem is found, then the token is matched with the token in the MatchmakingItem and a Wager is created.
     // If a MatchmakingItem is not found, then a MatchmakingItem is created with the token and the other parameters.
 
-    let matchmaking_item = MATCHMAKING
-        .range(deps.storage, None, None, Order::Ascending)
-        .find(|item| {
-            item.as_ref()
-                .map(|(_, v)| {
-                    v.against_currencies.contains(&currency)
-                        && against_currencies.contains(&v.currency)
-                        && v.expiry == expiry
-                        && v.amount == amount
-                        && v.expires_at > env.block.time
-                })
-                .unwrap_or(false)
-        })
-        .unwrap_or_else(|| {
-            Err(cosmwasm_std::StdError::NotFound {
-                kind: "matchmaking_item".into(),
-            })
-        });
-
-    if matchmaking_item.is_ok() {
-        let (
-            matchmaking_key,
-            MatchmakingItem {
-                currency: match_currency,
-                ..
-            },
-        ) = matchmaking_item?;
+    if let Some((
+        matchmaking_key,
+        MatchmakingItem {
+            basket: match_basket,
+            direction: match_direction,
+            amount: match_amount,
+            depositor: match_depositor,
+            ..
+        },
+    )) = find_matchmaking_item(
+        deps.storage,
+        &env,
+        &matchmaking_item,
+        &match_collections(deps.storage, config, &token)?,
+    )? {
+        let MatchmakingItem {
+            basket,
+            denom,
+            settlement_mode,
+            direction,
+            stake_type,
+            wager_type,
+            scoring_mode,
+            depositor,
+            ..
+        } = matchmaking_item;
 
         let against_token: Token = matchmaking_key;
-        let expires_at = env.block.time.plus_seconds(expiry);
 
-        let wager = Wager {
-            id: (token, against_token),
-            currencies: (currency, match_currency),
-            expires_at,
-            amount,
-        };
+        matchmaking().remove(deps.storage, against_token.clone())?;
 
-        wagers().save(deps.storage, (token, against_token), &wager)?;
+        // The fees of both sides of a token wager are held until it is settled
+        let (stakes, fee) = match stake_type {
+            StakeType::Funds => ((amount, match_amount), Uint128::zero()),
+            StakeType::Token => ((Uint128::zero(), Uint128::zero()), amount + match_amount),
+        };
 
-        MATCHMAKING.remove(deps.storage, matchmaking_key);
+        let expires_at = start_wager(
+            deps,
+            &env,
+            config,
+            (token.clone(), against_token),
+            (depositor, match_depositor),
+            (basket, match_basket),
+            (direction, match_direction),
+            denom,
+            stakes,
+            fee,
+            expiry,
+            settlement_mode,
+            stake_type,
+            wager_type,
+            scoring_mode,
+        )?;
 
         Ok(Response::new()
             .add_attribute("action", "wager")
-            .add_attribute("token_id", token.to_string())
+            .add_attribute("token_id", token.1.to_string())
             .add_attribute("expires_at", expires_at.to_string()))
     } else {
-        let expires_at = env.block.time.plus_seconds(config.matchmaking_expiry);
-        let matchmaking_item = MatchmakingItem {
-            expires_at,
-            currency,
-            against_currencies,
-            expiry,
-            amount,
-        };
-
-        MATCHMAKING.save(deps.storage, token, &matchmaking_item)?;
+        matchmaking().save(deps.storage, token.clone(), &matchmaking_item)?;
 
         Ok(Response::new()
             .add_attribute("action", "matchmake")
-            .add_attribute("token_id", token.to_string())
-            .add_attribute("expires_at", expires_at.to_string()))
+            .add_attribute("token_id", token.1.to_string())
+            .add_attribute("expires_at", matchmaking_item.expires_at.to_string()))
     }
 }
 
+// Save a new wager between two escrowed tokens, snapshotting the opening prices
+#[allow(clippy::too_many_arguments)]
+fn start_wager(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    id: WagerKey,
+    participants: (Addr, Addr),
+    baskets: (Basket, Basket),
+    directions: (Direction, Direction),
+    denom: Denom,
+    stakes: (Uint128, Uint128),
+    fee: Uint128,
+    expiry: u64,
+    settlement_mode: SettlementMode,
+    stake_type: StakeType,
+    wager_type: WagerType,
+    scoring_mode: ScoringMode,
+) -> Result<Timestamp, ContractError> {
+    let expires_at = env.block.time.plus_seconds(expiry);
+
+    // Snapshot the opening prices of the currencies of both baskets, from the same source
+    // as the closing prices
+    let load_prices = |basket: &Basket| {
+        basket
+            .iter()
+            .map(|(currency, _)| match settlement_mode {
+                SettlementMode::Spot => load_price(deps.as_ref(), config, currency, env.block.time),
+                SettlementMode::Twap { .. } => load_twap(
+                    deps.as_ref(),
+                    config,
+                    currency,
+                    env.block.time,
+                    env.block.time,
+                ),
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let opening_prices = (load_prices(&baskets.0)?, load_prices(&baskets.1)?);
+
+    // Snapshot the volatility factors volatility-normalized wagers divide the change of each
+    // currency by
+    let load_scoring_volatilities = |basket: &Basket| match scoring_mode {
+        ScoringMode::Raw => Ok(vec![Decimal::one(); basket.len()]),
+        ScoringMode::VolatilityNormalized => load_volatilities(deps.storage, basket),
+    };
+    let volatilities = (
+        load_scoring_volatilities(&baskets.0)?,
+        load_scoring_volatilities(&baskets.1)?,
+    );
+
+    let wager = Wager {
+        id,
+        baskets,
+        expires_at,
+        denom,
+        stakes,
+        fee,
+        opening_prices,
+        volatilities,
+        settlement_mode,
+        participants,
+        directions,
+        stake_type,
+        wager_type,
+        scoring_mode,
+    };
+
+    wagers().save(deps.storage, wager.id.clone(), &wager)?;
+
+    Ok(expires_at)
+}
+
+// Collections whose tokens a token can be matched with, every registered collection
+// when cross-collection matches are enabled
+fn match_collections(
+    storage: &dyn Storage,
+    config: &Config,
+    token: &Token,
+) -> StdResult<Vec<Addr>> {
+    if !config.cross_collection {
+        return Ok(vec![token.0.clone()]);
+    }
+
+    COLLECTIONS
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+// Find the item expiring first, then the lowest token, that `item` can be matched with, only
+// looking at the unexpired items in the buckets of the baskets it can be matched on in each
+// of `collections`
+fn find_matchmaking_item(
+    storage: &dyn Storage,
+    env: &Env,
+    item: &MatchmakingItem,
+    collections: &[Addr],
+) -> StdResult<Option<(Token, MatchmakingItem)>> {
+    let mut found: Option<(Token, MatchmakingItem)> = None;
+
+    // Race items are matched on the currencies they wager against, over/under items on their own
+    // and baskets of several currencies on the bucket of all such baskets
+    let buckets = match (&item.wager_type, single_currency(&item.basket)) {
+        (WagerType::Race, Some(_)) => item.against_currencies.clone(),
+        _ => vec![basket_bucket(&item.basket)],
+    };
+
+    for (against_bucket, collection) in buckets
+        .iter()
+        .flat_map(|b| collections.iter().map(move |c| (b, c)))
+    {
+        let bucket = (
+            (denom_key(&item.denom), item.match_amounts().1),
+            (item.expiry, against_bucket.clone()),
+            collection.clone(),
+        );
+        // Items expiring at the current time have expired, the lowest token expiring a
+        // nanosecond later bounds the rest
+        let unexpired =
+            Bound::inclusive((env.block.time.nanos() + 1, (Addr::unchecked(""), u64::MIN)));
+
+        for candidate in matchmaking().idx.bucket.sub_prefix(bucket).range(
+            storage,
+            Some(unexpired),
+            None,
+            Order::Ascending,
+        ) {
+            let (token, v) = candidate?;
+            if found
+                .as_ref()
+                .map(|(t, f)| (v.expires_at, &token) > (f.expires_at, t))
+                .unwrap_or(false)
+            {
+                break;
+            }
+            if v.denom == item.denom
+                && v.match_amounts().1 == item.match_amounts().0
+                && v.stake_type == item.stake_type
+                && v.settlement_mode == item.settlement_mode
+                && v.wager_type == item.wager_type
+                && v.scoring_mode == item.scoring_mode
+                && match item.wager_type {
+                    // Baskets of several currencies race any other basket
+                    WagerType::Race => {
+                        single_currency(&item.basket).map_or(v.basket != item.basket, |currency| {
+                            v.against_currencies.contains(currency)
+                        }) && v.direction == item.direction
+                    }
+                    WagerType::OverUnder { .. } => {
+                        v.basket == item.basket && v.direction != item.direction
+                    }
+                }
+                && v.expires_at > env.block.time
+                // Currencies may have been disabled while the item was resting
+                && v.basket
+                    .iter()
+                    .all(|(currency, _)| assert_enabled(storage, currency).is_ok())
+            {
+                found = Some((token, v));
+                break;
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+pub fn execute_update_prices(
+    deps: DepsMut,
+    env: Env,
+    prices: Vec<(CurrencyId, Decimal)>,
+) -> Result<Response, ContractError> {
+    for (currency, price) in prices.iter() {
+        load_currency(deps.storage, currency)?;
+        // A zero price could never be used as an opening price
+        if price.is_zero() {
+            return Err(ContractError::InvalidParameter {
+                param: "prices".into(),
+            });
+        }
+        PRICES.save(
+            deps.storage,
+            currency.to_string(),
+            &Price {
+                price: *price,
+                updated_at: env.block.time,
+            },
+        )?;
+        PRICE_OBSERVATIONS.save(
+            deps.storage,
+            (currency.to_string(), env.block.time.seconds()),
+            price,
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_prices")
+        .add_attribute("count", prices.len().to_string()))
+}
+
+pub fn execute_record_prices(
+    deps: DepsMut,
+    currencies: Vec<CurrencyId>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    for currency in currencies.iter() {
+        let feed_id = load_currency(deps.storage, currency)?.feed_id;
+        // Oracles that fail to respond are skipped, as when prices are loaded
+        for oracle in config.oracles.iter() {
+            if let Ok(price) = query_price(&deps.querier, oracle, &feed_id) {
+                if !price.price.is_zero() {
+                    ORACLE_OBSERVATIONS.save(
+                        deps.storage,
+                        (feed_id.clone(), &oracle.address, price.updated_at.nanos()),
+                        &price.price,
+                    )?;
+                }
+            }
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "record_prices")
+        .add_attribute("count", currencies.len().to_string()))
+}
+
 pub fn execute_cancel(
     deps: DepsMut,
     info: MessageInfo,
     token: Token,
 ) -> Result<Response, ContractError> {
-    let token_id = token;
+    let token_status = query_token_status(deps.as_ref(), token.clone())?.token_status;
 
+    // Verify that the sender is the depositor of the stake, even if the token has since changed hands
+    match token_status {
+        TokenStatus::Matchmaking(status) => {
+            if info.sender != status.depositor {
+                return Err(ContractError::Unauthorized {});
+            }
+            matchmaking().remove(deps.storage, token.clone())?;
+            // Token stakes can be free, with nothing to refund
+            let msg = if status.amount.is_zero() {
+                None
+            } else {
+                Some(send_stake(info.sender, &status.denom, status.amount)?)
+            };
+            let release = release_escrow(deps.storage, &token)?;
+            Ok(Response::new()
+                .add_submessages(msg)
+                .add_submessages(release)
+                .add_attribute("action", "cancel")
+                .add_attribute("token_id", token.1.to_string()))
+        }
+        TokenStatus::Challenge(status) => {
+            if info.sender != status.depositor {
+                return Err(ContractError::Unauthorized {});
+            }
+            challenges().remove(deps.storage, token.clone())?;
+            let msg = send_stake(info.sender, &status.denom, status.amount)?;
+            Ok(Response::new()
+                .add_submessage(msg)
+                .add_attribute("action", "cancel_challenge")
+                .add_attribute("token_id", token.1.to_string()))
+        }
+        _ => Err(ContractError::NotMatchmaking {}),
+    }
+}
+
+pub fn execute_take_over(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: Token,
+) -> Result<Response, ContractError> {
+    let paid = one_coin(&info)?;
+
+    take_over(
+        deps,
+        info.sender,
+        token,
+        Denom::Native(paid.denom),
+        paid.amount,
+    )
+}
+
+// Move the stake of a matchmaking item or challenge to `sender`, who paid `amount` of `denom`
+fn take_over(
+    deps: DepsMut,
+    sender: Addr,
+    token: Token,
+    denom: Denom,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    // Verify that the sender is the owner of the token
+    if sender != token_owner(deps.as_ref(), &token)? {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    let token_status = query_token_status(deps.as_ref(), token.clone())?.token_status;
+
+    let (stake_denom, stake_amount, previous_depositor) = match token_status {
+        TokenStatus::Matchmaking(status) => (status.denom, status.amount, status.depositor),
+        TokenStatus::Challenge(status) => (status.denom, status.amount, status.depositor),
+        _ => return Err(ContractError::NotMatchmaking {}),
+    };
+
+    if sender == previous_depositor {
+        return Err(ContractError::AlreadyDepositor {});
+    }
+
+    // The new owner pays the stake and the previous depositor gets theirs back
+    if denom != stake_denom || amount != stake_amount {
+        return Err(ContractError::InvalidParameter {
+            param: "amount".into(),
+        });
+    };
+
+    if let Some(item) = matchmaking().may_load(deps.storage, token.clone())? {
+        let item = MatchmakingItem {
+            depositor: sender.clone(),
+            ..item
+        };
+        matchmaking().save(deps.storage, token.clone(), &item)?;
+    } else {
+        let challenge = Challenge {
+            depositor: sender.clone(),
+            ..challenges().load(deps.storage, token.clone())?
+        };
+        challenges().save(deps.storage, token.clone(), &challenge)?;
+    }
+
+    let msg = send_stake(previous_depositor.clone(), &stake_denom, stake_amount)?;
+
+    Ok(Response::new()
+        .add_submessage(msg)
+        .add_attribute("action", "take_over")
+        .add_attribute("token_id", token.1.to_string())
+        .add_attribute("previous_depositor", previous_depositor)
+        .add_attribute("depositor", sender))
+}
+
+// Bounds on the amount of matchmaking items pruned per call
+const DEFAULT_PRUNE_LIMIT: u32 = 10;
+const MAX_PRUNE_LIMIT: u32 = 50;
+
+pub fn execute_prune_matchmaking(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let limit = limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+
+    // Items that can no longer be matched, oldest first
+    let expired = matchmaking()
+        .idx
+        .expires_at
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::exclusive((
+                env.block.time.nanos() + 1,
+                (Addr::unchecked(""), 0),
+            ))),
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut settlement = Settlement {
+        attributes: vec![
+            Attribute::new("action", "prune_matchmaking"),
+            Attribute::new("count", expired.len().to_string()),
+        ],
+        ..Default::default()
+    };
+    let mut keeper_rewards: BTreeMap<String, (Denom, Uint128)> = BTreeMap::new();
+
+    for (token, item) in expired {
+        let reward = item.amount * config.prune_reward_percent / Uint128::from(100u128);
+        keeper_rewards
+            .entry(denom_key(&item.denom))
+            .or_insert((item.denom.clone(), Uint128::zero()))
+            .1 += reward;
+
+        settlement
+            .transfers
+            .push((item.depositor, item.denom, item.amount - reward));
+        settlement
+            .attributes
+            .push(Attribute::new("token_id", token.1.to_string()));
+
+        matchmaking().remove(deps.storage, token.clone())?;
+        settlement
+            .messages
+            .extend(release_escrow(deps.storage, &token)?);
+    }
+
+    // Stakes can be in several denoms, the reward is paid in each of them
+    for (denom, amount) in keeper_rewards.into_values() {
+        settlement.attributes.push(Attribute::new(
+            "keeper_reward",
+            format!("{}{}", amount, denom_name(&denom)),
+        ));
+        settlement
+            .transfers
+            .push((info.sender.clone(), denom, amount));
+    }
+
+    settlement_response(vec![settlement], &config)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_challenge(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: Token,
+    opponent_token: Token,
+    currency: CurrencyId,
+    against_currency: CurrencyId,
+    amount: Uint128,
+    expiry: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let collection = load_collection(deps.storage, &token.0)?;
+
+    // Verify that the opponent token is from a collection it can be wagered against
+    load_collection(deps.storage, &opponent_token.0)?;
+    if !config.cross_collection && opponent_token.0 != token.0 {
+        return Err(ContractError::InvalidParameter {
+            param: "opponent_token".into(),
+        });
+    };
+
+    // Verify that the stake matches the challenged amount of an accepted denom
+    let stake = one_coin(&info)?;
+    if stake.amount != amount {
+        return Err(ContractError::InvalidParameter {
+            param: "amount".into(),
+        });
+    };
+    let denom = Denom::Native(stake.denom);
+    assert_stake(
+        deps.storage,
+        &denom,
+        amount,
+        collection.denom_amounts(&denom),
+    )?;
+
+    // Verify that the expiry is within the list of allowed expiries of the collection
+    if !collection
+        .expiries
+        .as_ref()
+        .unwrap_or(&config.expiries)
+        .contains(&expiry)
+    {
+        return Err(ContractError::InvalidParameter {
+            param: "expiry".into(),
+        });
+    };
+
+    if currency == against_currency {
+        return Err(ContractError::InvalidParameter {
+            param: "against_currency".into(),
+        });
+    };
+
+    // Verify that both currencies are enabled in the registry
+    assert_enabled(deps.storage, &currency)?;
+    assert_enabled(deps.storage, &against_currency)?;
+
+    if token == opponent_token {
+        return Err(ContractError::InvalidParameter {
+            param: "opponent_token".into(),
+        });
+    };
+
+    // Verify that the sender is the owner of the token and that the opponent token exists
+    if info.sender != token_owner(deps.as_ref(), &token)? {
+        return Err(ContractError::Unauthorized {});
+    };
+    token_owner(deps.as_ref(), &opponent_token)?;
+
+    // Verify that the token is not already wagered, matchmaking or challenging
+    let token_status = query_token_status(deps.as_ref(), token.clone())?.token_status;
+    if token_status != TokenStatus::None {
+        return Err(ContractError::AlreadyWagered {});
+    };
+
+    challenges().save(
+        deps.storage,
+        token.clone(),
+        &Challenge {
+            opponent_token: opponent_token.clone(),
+            currency,
+            against_currency,
+            denom,
+            amount,
+            expiry,
+            depositor: info.sender,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "challenge")
+        .add_attribute("token_id", token.1.to_string())
+        .add_attribute("opponent_token_id", opponent_token.1.to_string()))
+}
+
+pub fn execute_accept_challenge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token: Token,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let challenge = challenges()
+        .may_load(deps.storage, token.clone())?
+        .ok_or(ContractError::NotChallenged {})?;
+    let opponent_token = challenge.opponent_token.clone();
+
+    // Verify that the sender is the owner of the challenged token
+    if info.sender != token_owner(deps.as_ref(), &opponent_token)? {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    // Verify that the challenged token is not already wagered, matchmaking or challenging
+    let token_status = query_token_status(deps.as_ref(), opponent_token.clone())?.token_status;
+    if token_status != TokenStatus::None {
+        return Err(ContractError::AlreadyWagered {});
+    };
+
+    // Verify that both collections are still registered and both currencies still enabled,
+    // either may have changed since the challenge was made
+    load_collection(deps.storage, &token.0)?;
+    load_collection(deps.storage, &opponent_token.0)?;
+    assert_enabled(deps.storage, &challenge.currency)?;
+    assert_enabled(deps.storage, &challenge.against_currency)?;
+
+    let paid = one_coin(&info)?;
+    if Denom::Native(paid.denom) != challenge.denom || paid.amount != challenge.amount {
+        return Err(ContractError::InvalidParameter {
+            param: "amount".into(),
+        });
+    };
+
+    challenges().remove(deps.storage, token.clone())?;
+
+    let expires_at = start_wager(
+        deps,
+        &env,
+        &config,
+        (token, challenge.opponent_token),
+        (challenge.depositor, info.sender),
+        (
+            WagerCurrency::Single(challenge.currency).into_basket(),
+            WagerCurrency::Single(challenge.against_currency).into_basket(),
+        ),
+        (Direction::Long, Direction::Long),
+        challenge.denom,
+        (challenge.amount, challenge.amount),
+        Uint128::zero(),
+        challenge.expiry,
+        SettlementMode::Spot,
+        StakeType::Funds,
+        WagerType::Race,
+        ScoringMode::Raw,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_challenge")
+        .add_attribute("token_id", opponent_token.1.to_string())
+        .add_attribute("expires_at", expires_at.to_string()))
+}
+
+pub fn execute_claim_refund(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token: Token,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     // Verify that the sender is the owner of the token
-    let token_owner = Cw721Contract(config.collection_address)
-        .owner_of(&deps.querier, token_id.to_string(), true)?
-        .owner;
-    if info.sender != token_owner {
+    if info.sender != token_owner(deps.as_ref(), &token)? {
         return Err(ContractError::Unauthorized {});
     };
 
-    let token_status = query_token_status(deps.as_ref(), token)?.token_status;
+    let wager = match query_token_status(deps.as_ref(), token.clone())?.token_status {
+        TokenStatus::Wager(status) => wagers().load(
+            deps.storage,
+            (
+                (
+                    status.wagers.0.token.collection,
+                    status.wagers.0.token.token_id,
+                ),
+                (
+                    status.wagers.1.token.collection,
+                    status.wagers.1.token.token_id,
+                ),
+            ),
+        )?,
+        _ => return Err(ContractError::NotWagered {}),
+    };
 
-    match token_status {
-        TokenStatus::Matchmaking(status) => {
-            MATCHMAKING.remove(deps.storage, token);
-            let msg = send_tokens(info.sender, coin(status.amount.u128(), NATIVE_DENOM))?;
-            Ok(Response::new()
-                .add_submessage(msg)
-                .add_attribute("action", "cancel")
-                .add_attribute("token_id", token.to_string()))
+    // Verify that the wager was left unsettled past the grace period
+    if env.block.time
+        < wager
+            .expires_at
+            .plus_seconds(config.settlement_grace_period)
+    {
+        return Err(ContractError::GracePeriodActive {});
+    }
+
+    // Verify that no result is pending, an undisputed result can be finalized instead and a
+    // disputed one is voided once left unresolved for the grace period after the dispute window
+    let mut bond_refund = None;
+    if let Some(result) = PROPOSED_RESULTS.may_load(deps.storage, wager.id.clone())? {
+        match result.dispute {
+            Some(dispute)
+                if env.block.time
+                    >= result
+                        .dispute_ends_at
+                        .plus_seconds(config.settlement_grace_period) =>
+            {
+                PROPOSED_RESULTS.remove(deps.storage, wager.id.clone());
+                if !dispute.bond.is_zero() {
+                    bond_refund = Some(send_tokens(
+                        dispute.disputer,
+                        coin(dispute.bond.u128(), NATIVE_DENOM),
+                    )?);
+                }
+            }
+            _ => return Err(ContractError::ResultPending {}),
         }
-        _ => Err(ContractError::NotMatchmaking {}),
     }
+
+    // Send each side's stake back according to the payout policy, token stakes are only released
+    let msgs = match wager.stake_type {
+        StakeType::Funds => vec![
+            send_stake(
+                payout_address(deps.as_ref(), &config, &wager, &wager.id.0)?,
+                &wager.denom,
+                wager.stakes.0,
+            )?,
+            send_stake(
+                payout_address(deps.as_ref(), &config, &wager, &wager.id.1)?,
+                &wager.denom,
+                wager.stakes.1,
+            )?,
+        ],
+        // The flat fee of a token wager was earned when it was matched
+        StakeType::Token if wager.fee.is_zero() => vec![],
+        StakeType::Token => vec![send_tokens(
+            config.fee_address.clone(),
+            coin(wager.fee.u128(), NATIVE_DENOM),
+        )?],
+    };
+
+    wagers().remove(deps.storage, wager.id.clone())?;
+
+    let releases = vec![
+        release_escrow(deps.storage, &wager.id.0)?,
+        release_escrow(deps.storage, &wager.id.1)?,
+    ];
+
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_submessages(bond_refund)
+        .add_submessages(releases.into_iter().flatten())
+        .add_attribute("action", "claim_refund")
+        .add_attribute("status", "voided")
+        .add_attribute("token_id", token.1.to_string()))
+}
+
+// Load a wager by its key, in either order
+fn load_wager(storage: &dyn Storage, wager_key: (Token, Token)) -> StdResult<Wager> {
+    wagers()
+        .load(storage, wager_key.clone())
+        .or_else(|_| wagers().load(storage, (wager_key.1, wager_key.0)))
+}
+
+// Attributes and transfers produced by settling a wager, so that several
+// settlements can be merged into a single response
+#[derive(Default)]
+pub struct Settlement {
+    pub attributes: Vec<Attribute>,
+    pub transfers: Vec<(Addr, Denom, Uint128)>,
+    pub fairburn: Uint128,
+    pub messages: Vec<SubMsg<StargazeMsgWrapper>>,
+}
+
+// Merge settlements into one response, sending a single transfer per recipient
+fn settlement_response(
+    settlements: Vec<Settlement>,
+    config: &Config,
+) -> Result<Response, ContractError> {
+    let mut res = Response::new();
+    let mut transfers: BTreeMap<(Addr, String), (Denom, Uint128)> = BTreeMap::new();
+    let mut fairburn = Uint128::zero();
+
+    for settlement in settlements {
+        res = res
+            .add_attributes(settlement.attributes)
+            .add_submessages(settlement.messages);
+        for (recipient, denom, amount) in settlement.transfers {
+            transfers
+                .entry((recipient, denom_key(&denom)))
+                .or_insert((denom, Uint128::zero()))
+                .1 += amount;
+        }
+        fairburn += settlement.fairburn;
+    }
+
+    for ((recipient, _), (denom, amount)) in transfers {
+        if !amount.is_zero() {
+            res = res.add_submessage(send_stake(recipient, &denom, amount)?);
+        }
+    }
+
+    if !fairburn.is_zero() {
+        fair_burn(fairburn.u128(), Some(config.fee_address.clone()), &mut res);
+    }
+
+    Ok(res)
 }
 
-#[allow(clippy::comparison_chain)]
 pub fn execute_set_winner(
     deps: DepsMut,
     env: Env,
+    info: MessageInfo,
     wager_key: (Token, Token),
-    prev_prices: (Decimal, Decimal),
-    current_prices: (Decimal, Decimal),
+    attestations: Option<Vec<PriceAttestation>>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
+    let settlement = set_winner(
+        deps,
+        &env,
+        &config,
+        &info.sender,
+        wager_key,
+        attestations.as_deref(),
+    )?;
+
+    settlement_response(vec![settlement], &config)
+}
+
+pub fn execute_set_winners(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    results: Vec<WagerResult>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Failed settlements are reported instead of aborting the whole batch
+    let settlements = results
+        .into_iter()
+        .map(|result| {
+            let key = format!(
+                "{}:{}-{}:{}",
+                result.wager_key.0 .0,
+                result.wager_key.0 .1,
+                result.wager_key.1 .0,
+                result.wager_key.1 .1
+            );
+            match set_winner(
+                deps.branch(),
+                &env,
+                &config,
+                &info.sender,
+                result.wager_key,
+                result.attestations.as_deref(),
+            ) {
+                Ok(mut settlement) => {
+                    settlement
+                        .attributes
+                        .insert(0, Attribute::new("settled", key));
+                    settlement
+                }
+                Err(err) => Settlement {
+                    attributes: vec![Attribute::new("failed", format!("{}: {}", key, err))],
+                    ..Default::default()
+                },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(settlement_response(settlements, &config)?
+        .add_attribute("action", "set_winners")
+        .add_attribute("keeper", info.sender))
+}
+
+#[allow(clippy::comparison_chain)]
+fn set_winner(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    sender: &Addr,
+    wager_key: (Token, Token),
+    attestations: Option<&[PriceAttestation]>,
+) -> Result<Settlement, ContractError> {
     // Get the wager info
-    let wager = wagers()
-        .load(deps.storage, wager_key)
-        .or_else(|_| wagers().load(deps.storage, (wager_key.1, wager_key.0)))?;
+    let wager = load_wager(deps.storage, wager_key)?;
 
     // Verify that the wager has expired
     if env.block.time < wager.expires_at {
         return Err(ContractError::WagerActive {});
     }
 
-    // Remove the wager
-    wagers().remove(deps.storage, wager_key)?;
+    // Verify that a result has not already been proposed
+    if PROPOSED_RESULTS.has(deps.storage, wager.id.clone()) {
+        return Err(ContractError::ResultPending {});
+    }
+
+    // Attested prices are spot prices, they cannot stand in for a TWAP, and they
+    // cannot replace the oracles the wager was matched against
+    if attestations.is_some()
+        && (wager.settlement_mode != SettlementMode::Spot || !config.oracles.is_empty())
+    {
+        return Err(ContractError::InvalidParameter {
+            param: "attestations".into(),
+        });
+    }
+
+    // Read the closing prices from the attestations, the oracles or the price feed,
+    // or average them over the TWAP window from the price observations
+    let load_closing_prices = |basket: &Basket| {
+        basket
+            .iter()
+            .map(|(currency, _)| match wager.settlement_mode {
+                SettlementMode::Spot => load_attested_price(
+                    deps.as_ref(),
+                    config,
+                    attestations,
+                    currency,
+                    wager.expires_at,
+                ),
+                SettlementMode::Twap { window } => load_twap(
+                    deps.as_ref(),
+                    config,
+                    currency,
+                    wager.expires_at.minus_seconds(window),
+                    wager.expires_at,
+                ),
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let volatilities = &wager.volatilities;
 
-    // Determine the winner of the wager
-    let token_1_change = Decimal::from_ratio(current_prices.0.atomics(), prev_prices.0.atomics());
-    let token_2_change = Decimal::from_ratio(current_prices.1.atomics(), prev_prices.1.atomics());
+    let prev_prices = &wager.opening_prices;
+    let current_prices = (
+        load_closing_prices(&wager.baskets.0)?,
+        load_closing_prices(&wager.baskets.1)?,
+    );
 
-    let winner;
+    // Determine the winner of the wager by the performance of each side in its direction,
+    // or `None` if it is a tie. Over/under wagers measure the closing price against the strike
+    let (token_1_change, token_2_change) = match wager.wager_type {
+        WagerType::Race => (
+            performance(
+                &wager.baskets.0,
+                &prev_prices.0,
+                &current_prices.0,
+                &volatilities.0,
+                &wager.directions.0,
+            ),
+            performance(
+                &wager.baskets.1,
+                &prev_prices.1,
+                &current_prices.1,
+                &volatilities.1,
+                &wager.directions.1,
+            ),
+        ),
+        WagerType::OverUnder { strike } => (
+            performance(
+                &wager.baskets.0,
+                &[strike],
+                &current_prices.0,
+                &volatilities.0,
+                &wager.directions.0,
+            ),
+            performance(
+                &wager.baskets.1,
+                &[strike],
+                &current_prices.1,
+                &volatilities.1,
+                &wager.directions.1,
+            ),
+        ),
+    };
 
-    if token_1_change > token_2_change {
-        winner = wager_key.0
+    let winner = if token_1_change > token_2_change {
+        Some(wager.id.0.clone())
     } else if token_2_change > token_1_change {
-        winner = wager_key.1
+        Some(wager.id.1.clone())
     } else {
-        let res = Response::new().add_attribute("action", "wager_tie");
-
-        // If the wager is a tie, send the wager amount back to both parties
-        let msgs = vec![
-            send_tokens(
-                deps.api.addr_validate(
-                    &Cw721Contract(config.collection_address.clone())
-                        .owner_of(&deps.querier, wager.id.0.to_string(), true)?
-                        .owner,
-                )?,
-                coin(wager.amount.u128(), NATIVE_DENOM),
-            )?,
-            send_tokens(
-                deps.api.addr_validate(
-                    &Cw721Contract(config.collection_address)
-                        .owner_of(&deps.querier, wager.id.1.to_string(), true)?
-                        .owner,
-                )?,
-                coin(wager.amount.u128(), NATIVE_DENOM),
-            )?,
-        ];
-        return Ok(res.add_submessages(msgs));
+        None
     };
 
-    let winner_addr = Cw721Contract(config.collection_address)
-        .owner_of(&deps.querier, winner.to_string(), true)?
-        .owner;
+    // Without a dispute window the result is paid out right away
+    if config.dispute_window == 0 {
+        return settle_wager(deps, config, wager, winner, sender.clone());
+    }
 
-    // Pay out the winner
-    let wager_total = wager.amount * Uint128::from(2u128);
+    let dispute_ends_at = env.block.time.plus_seconds(config.dispute_window);
 
-    let app_fee = wager_total * config.fee_percent / Uint128::from(100u128);
-    let fairburn_fee = wager_total * config.fairburn_percent / Uint128::from(100u128);
+    PROPOSED_RESULTS.save(
+        deps.storage,
+        wager.id,
+        &ProposedResult {
+            winner: winner.clone(),
+            closing_prices: current_prices,
+            proposer: sender.clone(),
+            dispute_ends_at,
+            dispute: None,
+        },
+    )?;
 
-    let winner_amount = wager_total - app_fee - fairburn_fee;
+    Ok(Settlement {
+        attributes: vec![
+            Attribute::new("action", "propose_result"),
+            Attribute::new(
+                "winner",
+                winner
+                    .map(|t| t.1.to_string())
+                    .unwrap_or_else(|| "tie".into()),
+            ),
+            Attribute::new("keeper", sender.clone()),
+            Attribute::new("dispute_ends_at", dispute_ends_at.to_string()),
+        ],
+        ..Default::default()
+    })
+}
 
-    // Charge fee & fair burn
-    let mut res = Response::new()
-        .add_attribute("action", "set_winner")
-        .add_attribute("winner", winner_addr.clone());
+// Signed change of the closing prices of a basket from the opening prices or strike, weighted
+// by the basket and divided by the volatility factor of each currency, in the direction picked
+// by a side and in units of `Decimal::one()`
+fn performance(
+    basket: &Basket,
+    reference_prices: &[Decimal],
+    closing_prices: &[Decimal],
+    volatilities: &[Decimal],
+    direction: &Direction,
+) -> i128 {
+    let change = basket
+        .iter()
+        .zip(reference_prices.iter().zip(closing_prices))
+        .zip(volatilities)
+        .map(
+            |(((_, weight), (reference_price, closing_price)), volatility)| {
+                let ratio = Decimal::from_ratio(closing_price.atomics(), reference_price.atomics());
+                // Decimals are unsigned, so rises and falls are scaled separately
+                if ratio >= Decimal::one() {
+                    ((ratio - Decimal::one()) * *weight / *volatility)
+                        .atomics()
+                        .u128() as i128
+                } else {
+                    -(((Decimal::one() - ratio) * *weight / *volatility)
+                        .atomics()
+                        .u128() as i128)
+                }
+            },
+        )
+        .sum::<i128>();
 
-    let fee_msg = send_tokens(
-        config.fee_address.clone(),
-        coin(app_fee.u128(), NATIVE_DENOM),
-    )?;
-    let winner_msg = send_tokens(
-        deps.api.addr_validate(&winner_addr)?,
-        coin(winner_amount.u128(), NATIVE_DENOM),
-    )?;
+    match direction {
+        Direction::Long => change,
+        Direction::Short => -change,
+    }
+}
+
+pub fn execute_dispute_result(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token: Token,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let wager_key = match query_token_status(deps.as_ref(), token.clone())?.token_status {
+        TokenStatus::Wager(status) => (
+            (
+                status.wagers.0.token.collection,
+                status.wagers.0.token.token_id,
+            ),
+            (
+                status.wagers.1.token.collection,
+                status.wagers.1.token.token_id,
+            ),
+        ),
+        _ => return Err(ContractError::NotWagered {}),
+    };
+
+    // Verify that the sender is the account the token's side of the wager is paid to
+    let wager = load_wager(deps.storage, wager_key.clone())?;
+    if info.sender != payout_address(deps.as_ref(), &config, &wager, &token)? {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    let mut result = PROPOSED_RESULTS.load(deps.storage, wager_key.clone())?;
+
+    // Verify that the result can still be disputed
+    if env.block.time >= result.dispute_ends_at {
+        return Err(ContractError::DisputeWindowClosed {});
+    }
+    if result.dispute.is_some() {
+        return Err(ContractError::ResultDisputed {});
+    }
+
+    // Verify that the bond was paid
+    let bond = if config.dispute_bond.is_zero() {
+        Uint128::zero()
+    } else {
+        must_pay(&info, NATIVE_DENOM)?
+    };
+    if bond != config.dispute_bond {
+        return Err(ContractError::InvalidParameter {
+            param: "bond".into(),
+        });
+    }
+
+    result.dispute = Some(Dispute {
+        disputer: info.sender.clone(),
+        bond,
+    });
+    PROPOSED_RESULTS.save(deps.storage, wager_key, &result)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "dispute_result")
+        .add_attribute("token_id", token.1.to_string())
+        .add_attribute("disputer", info.sender))
+}
+
+pub fn execute_resolve_dispute(
+    deps: DepsMut,
+    wager_key: (Token, Token),
+    winner: Option<Token>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let wager = load_wager(deps.storage, wager_key)?;
+    let result = PROPOSED_RESULTS.load(deps.storage, wager.id.clone())?;
+
+    let dispute = match result.dispute {
+        Some(dispute) => dispute,
+        None => return Err(ContractError::NotDisputed {}),
+    };
+
+    // Verify that the winner is part of the wager
+    if let Some(winner) = &winner {
+        if *winner != wager.id.0 && *winner != wager.id.1 {
+            return Err(ContractError::InvalidParameter {
+                param: "winner".into(),
+            });
+        }
+    }
+
+    // The bond is returned if the dispute changed the result, otherwise it is kept as a fee
+    let bond_recipient = if winner != result.winner {
+        dispute.disputer
+    } else {
+        config.fee_address.clone()
+    };
+
+    let mut settlement = settle_wager(deps, &config, wager, winner, result.proposer)?;
+    settlement
+        .attributes
+        .push(Attribute::new("bond_recipient", bond_recipient.clone()));
+    settlement.transfers.push((
+        bond_recipient,
+        Denom::Native(NATIVE_DENOM.to_string()),
+        dispute.bond,
+    ));
+
+    settlement_response(vec![settlement], &config)
+}
+
+pub fn execute_finalize_result(
+    deps: DepsMut,
+    env: Env,
+    wager_key: (Token, Token),
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let wager = load_wager(deps.storage, wager_key)?;
+    let result = PROPOSED_RESULTS.load(deps.storage, wager.id.clone())?;
+
+    // Verify that the dispute window is over and the result was not disputed
+    if env.block.time < result.dispute_ends_at {
+        return Err(ContractError::DisputeWindowActive {});
+    }
+    if result.dispute.is_some() {
+        return Err(ContractError::ResultDisputed {});
+    }
+
+    let settlement = settle_wager(deps, &config, wager, result.winner, result.proposer)?;
+
+    settlement_response(vec![settlement], &config)
+}
+
+// Pay out the winner, or refund both sides on a tie, and remove the wager
+fn settle_wager(
+    deps: DepsMut,
+    config: &Config,
+    wager: Wager,
+    winner: Option<Token>,
+    keeper: Addr,
+) -> Result<Settlement, ContractError> {
+    let owner_of = |token: &Token| payout_address(deps.as_ref(), config, &wager, token);
+
+    let settlement = match &winner {
+        // If the wager is a tie, send each side's stake back
+        None => {
+            // The flat fee of a token wager is still paid on a tie
+            let keeper_reward = wager.fee * config.keeper_reward_percent / Uint128::from(100u128);
+
+            Settlement {
+                attributes: vec![
+                    Attribute::new("action", "wager_tie"),
+                    Attribute::new("keeper", keeper.clone()),
+                    Attribute::new("keeper_reward", keeper_reward),
+                ],
+                transfers: vec![
+                    (owner_of(&wager.id.0)?, wager.denom.clone(), wager.stakes.0),
+                    (owner_of(&wager.id.1)?, wager.denom.clone(), wager.stakes.1),
+                    (
+                        config.fee_address.clone(),
+                        Denom::Native(NATIVE_DENOM.to_string()),
+                        wager.fee - keeper_reward,
+                    ),
+                    (
+                        keeper,
+                        Denom::Native(NATIVE_DENOM.to_string()),
+                        keeper_reward,
+                    ),
+                ],
+                ..Default::default()
+            }
+        }
+        Some(winner) => {
+            let winner_addr = owner_of(winner)?;
+
+            // Pay out the winner
+            let wager_total = wager.stakes.0 + wager.stakes.1;
+
+            // Cross-collection wagers pay the highest fee of both collections
+            let fee_percent = wager_fee_percent(deps.storage, config, &wager.id)?;
+
+            let app_fee = wager_total * fee_percent / Uint128::from(100u128);
+            let fairburn_fee = wager_total * config.fairburn_percent / Uint128::from(100u128);
+
+            let winner_amount = wager_total - app_fee - fairburn_fee;
+
+            // Token wagers have no pot, the flat fee of both sides is their app fee
+            let app_fee = app_fee + wager.fee;
+
+            // The keeper reward is taken from the app fee
+            let keeper_reward = app_fee * config.keeper_reward_percent / Uint128::from(100u128);
+            let app_fee = app_fee - keeper_reward;
+
+            // Only the native denom can be fair burned, other denoms go to the fee collector instead
+            let (app_fee, fairburn_fee) = if wager.denom == Denom::Native(NATIVE_DENOM.to_string())
+            {
+                (app_fee, fairburn_fee)
+            } else {
+                (app_fee + fairburn_fee, Uint128::zero())
+            };
+
+            // Charge fee & fair burn
+            Settlement {
+                attributes: vec![
+                    Attribute::new("action", "set_winner"),
+                    Attribute::new("winner", winner_addr.clone()),
+                    Attribute::new("keeper", keeper.clone()),
+                    Attribute::new("keeper_reward", keeper_reward),
+                ],
+                transfers: vec![
+                    (config.fee_address.clone(), wager.denom.clone(), app_fee),
+                    (winner_addr, wager.denom.clone(), winner_amount),
+                    (keeper, wager.denom.clone(), keeper_reward),
+                ],
+                fairburn: fairburn_fee,
+                ..Default::default()
+            }
+        }
+    };
+
+    // In a token wager the token of the loser goes to the winner instead of its depositor
+    let mut messages = vec![];
+    if let (StakeType::Token, Some(winner)) = (&wager.stake_type, &winner) {
+        let loser = if *winner == wager.id.0 {
+            &wager.id.1
+        } else {
+            &wager.id.0
+        };
+        let winner_addr = payout_address(deps.as_ref(), config, &wager, winner)?;
+
+        ESCROWED.remove(deps.storage, loser.clone());
+        messages.push(transfer_nft(loser.0.clone(), winner_addr, loser.1)?);
+    }
+
+    // Remove the wager and return escrowed tokens to their depositors
+    wagers().remove(deps.storage, wager.id.clone())?;
+    PROPOSED_RESULTS.remove(deps.storage, wager.id.clone());
+
+    let releases = vec![
+        release_escrow(deps.storage, &wager.id.0)?,
+        release_escrow(deps.storage, &wager.id.1)?,
+    ];
 
-    fair_burn(fairburn_fee.u128(), Some(config.fee_address), &mut res);
+    messages.extend(releases.into_iter().flatten());
 
-    Ok(res.add_submessages(vec![fee_msg, winner_msg]))
+    Ok(Settlement {
+        messages,
+        ..settlement
+    })
 }
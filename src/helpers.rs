@@ -1,5 +1,7 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, BankMsg, Coin, StdResult, SubMsg};
+use cosmwasm_std::{coin, to_binary, Addr, BankMsg, Coin, StdResult, SubMsg, Uint128, WasmMsg};
+use cw20::{Cw20ExecuteMsg, Denom};
+use cw721::Cw721ExecuteMsg;
 use sg_std::StargazeMsgWrapper;
 
 /// CwWagerContract is a wrapper around Addr that provides a lot of helpers
@@ -24,3 +26,44 @@ pub fn send_tokens(to: Addr, balance: Coin) -> StdResult<SubMsg<StargazeMsgWrapp
 
     Ok(exec)
 }
+
+// Send a stake in a native denom or a cw20 token to another address
+pub fn send_stake(
+    to: Addr,
+    denom: &Denom,
+    amount: Uint128,
+) -> StdResult<SubMsg<StargazeMsgWrapper>> {
+    match denom {
+        Denom::Native(denom) => send_tokens(to, coin(amount.u128(), denom)),
+        Denom::Cw20(address) => {
+            let msg = WasmMsg::Execute {
+                contract_addr: address.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: to.into_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            };
+
+            Ok(SubMsg::<StargazeMsgWrapper>::new(msg))
+        }
+    }
+}
+
+// Transfer an NFT held by the contract to another address
+pub fn transfer_nft(
+    collection: Addr,
+    to: Addr,
+    token_id: u64,
+) -> StdResult<SubMsg<StargazeMsgWrapper>> {
+    let msg = WasmMsg::Execute {
+        contract_addr: collection.into_string(),
+        msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: to.into_string(),
+            token_id: token_id.to_string(),
+        })?,
+        funds: vec![],
+    };
+
+    Ok(SubMsg::<StargazeMsgWrapper>::new(msg))
+}
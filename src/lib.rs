@@ -1,9 +1,13 @@
+pub mod collection;
 pub mod config;
 pub mod contract;
+pub mod currency;
+pub mod denom;
 mod error;
 pub mod execute;
 pub mod helpers;
 pub mod msg;
+pub mod oracle;
 pub mod state;
 
 #[cfg(test)]
@@ -1,9 +1,15 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Timestamp, Uint128};
+use cw20::{Cw20ReceiveMsg, Denom};
+use cw721::Cw721ReceiveMsg;
 
 use crate::{
     config::ParamInfo,
-    state::{Config, Currency, MatchmakingItemExport, Token, TokenStatus, WagerExport},
+    state::{
+        Basket, ChallengeExport, CollectionConfig, Config, CurrencyId, Direction,
+        MatchmakingItemExport, OracleQueryFormat, PayoutPolicy, ProposedResult, ScoringMode,
+        SettlementMode, Signer, Token, TokenStatus, WagerExport, WagerType,
+    },
 };
 
 #[cw_serde]
@@ -11,37 +17,280 @@ pub struct InstantiateMsg {
     pub max_currencies: u8,
     pub amounts: Vec<Uint128>,
     pub expiries: Vec<u64>,
+    pub twap_windows: Vec<u64>,
     pub fee_bps: u64,
     pub fairburn_bps: u64,
     pub fee_address: String,
     pub collection_address: String,
     pub matchmaking_expiry: u64,
+    pub oracles: Vec<OracleInfo>,
+    pub min_price_sources: u8,
+    pub max_price_age: u64,
+    pub signers: Vec<Signer>,
+    pub signature_threshold: u8,
+    pub attestation_window: u64,
+    pub keeper_reward_bps: u64,
+    pub prune_reward_bps: u64,
+    pub settlement_grace_period: u64,
+    pub dispute_window: u64,
+    pub dispute_bond: Uint128,
+    pub arbiter: Option<String>,
+    /// Defaults to paying the owners of the tokens at settlement
+    pub payout_policy: Option<PayoutPolicy>,
+    pub token_wager_fee: Uint128,
+    pub currencies: Vec<CurrencyId>,
+    pub cross_collection: bool,
+}
+
+/// A single currency, or a weighted basket of currencies (ex: `[["atom","0.5"],["osmo","0.3"],["stars","0.2"]]`)
+#[cw_serde]
+#[serde(untagged)]
+pub enum WagerCurrency {
+    Single(CurrencyId),
+    Basket(Basket),
+}
+
+impl WagerCurrency {
+    pub fn into_basket(self) -> Basket {
+        match self {
+            WagerCurrency::Single(currency) => vec![(currency, Decimal::one())],
+            WagerCurrency::Basket(basket) => basket,
+        }
+    }
+}
+
+impl From<&str> for WagerCurrency {
+    fn from(currency: &str) -> Self {
+        WagerCurrency::Single(currency.to_string())
+    }
+}
+
+#[cw_serde]
+pub struct OracleInfo {
+    pub address: String,
+    pub query_format: OracleQueryFormat,
+}
+
+#[cw_serde]
+pub struct PriceReport {
+    pub currency: CurrencyId,
+    pub price: Decimal,
+    pub timestamp: Timestamp,
+}
+
+#[cw_serde]
+pub struct ReportSignature {
+    pub pubkey: Binary,
+    pub signature: Binary,
+}
+
+/// A price report signed over `sha256(json(report))` by the configured signers
+#[cw_serde]
+pub struct PriceAttestation {
+    pub report: PriceReport,
+    pub signatures: Vec<ReportSignature>,
 }
 
 #[cw_serde]
+pub struct WagerResult {
+    pub wager_key: (Token, Token),
+    pub attestations: Option<Vec<PriceAttestation>>,
+}
+
+#[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     /// Privileged
     UpdateConfig {
         params: ParamInfo,
     },
 
-    /// Use Authz
+    /// Pushes prices to the price feed used when no oracles are configured
+    UpdatePrices {
+        prices: Vec<(CurrencyId, Decimal)>,
+    },
+    /// Records the latest price of every oracle for `currencies`, so that wagers expiring
+    /// before the next oracle update can still be settled on it
+    RecordPrices {
+        currencies: Vec<CurrencyId>,
+    },
+
+    /// Adds a currency to the registry, or re-enables it
+    AddCurrency {
+        id: CurrencyId,
+        feed_id: Option<String>,
+    },
+    /// Stops new wagers on a currency, existing wagers can still be settled
+    DisableCurrency {
+        id: CurrencyId,
+    },
+    /// Sets the id used to query the price of a currency from oracles
+    SetCurrencyFeed {
+        id: CurrencyId,
+        feed_id: String,
+    },
+    /// Sets the volatility factor of a currency, can also be called by the configured oracles
+    SetCurrencyVolatility {
+        id: CurrencyId,
+        volatility: Decimal,
+    },
+
+    /// Accepts a denom as stakes with the given amount options, or updates them
+    SetDenom {
+        denom: Denom,
+        amounts: Vec<Uint128>,
+    },
+    /// Stops new wagers in a denom, existing wagers are still paid out in it
+    RemoveDenom {
+        denom: Denom,
+    },
+
+    /// Accepts the tokens of a collection, or updates its options.
+    /// Unset options fall back to the config
+    SetCollection {
+        address: String,
+        /// Amount options by denom, replacing those of the listed denoms
+        amounts: Option<Vec<(Denom, Vec<Uint128>)>>,
+        expiries: Option<Vec<u64>>,
+        fee_bps: Option<u64>,
+        matchmaking_expiry: Option<u64>,
+    },
+    /// Stops new wagers with the tokens of a collection, existing wagers can still be settled
+    RemoveCollection {
+        address: String,
+    },
+
+    /// Admin or arbiter only, settles a disputed result
+    ResolveDispute {
+        wager_key: (Token, Token),
+        winner: Option<Token>,
+    },
+
+    /// Admin only, unless oracles are configured or closing prices are attested
+    /// Attestations must cover every currency of the wager, and cannot settle TWAP wagers
+    /// or be relayed while oracles are configured
+    /// Proposes the result when a dispute window is configured
     SetWinner {
         wager_key: (Token, Token),
-        prev_prices: (Decimal, Decimal),
-        current_prices: (Decimal, Decimal),
+        attestations: Option<Vec<PriceAttestation>>,
+    },
+    /// Settles many wagers at once, reporting failures in the attributes
+    SetWinners {
+        results: Vec<WagerResult>,
+    },
+    /// Pays out a proposed result once the dispute window is over
+    FinalizeResult {
+        wager_key: (Token, Token),
     },
 
+    /// Escrows an NFT sent with `SendNft`, see `ReceiveMsg`
+    ReceiveNft(Cw721ReceiveMsg),
+    /// Pays a stake in an accepted cw20 token, see `Cw20HookMsg`
+    Receive(Cw20ReceiveMsg),
+    /// Deposits STARS to pay the stake of escrowed wagers
+    Deposit {},
+    /// Withdraws the STARS left from `Deposit`
+    Withdraw {},
     /// User-facing
     Wager {
         token: Token,
-        currency: Currency,
-        against_currencies: Vec<Currency>,
+        /// Holds up to `max_currencies`, baskets of several currencies are only matched with
+        /// each other and have no `against_currencies`
+        currency: WagerCurrency,
+        against_currencies: Vec<CurrencyId>,
         expiry: u64,
+        /// Defaults to `SettlementMode::Spot`
+        settlement_mode: Option<SettlementMode>,
+        /// Defaults to `Direction::Long`
+        direction: Option<Direction>,
+        /// Defaults to `WagerType::Race`, over/under wagers have no `against_currencies`
+        wager_type: Option<WagerType>,
+        /// Stake required from the opponent to wager at odds, defaults to the sent amount
+        against_amount: Option<Uint128>,
+        /// Defaults to `ScoringMode::Raw`, wagers only match others with the same scoring mode
+        scoring_mode: Option<ScoringMode>,
     },
     Cancel {
         token: Token,
     },
+    /// Replaces the depositor of a matchmaking item or challenge with the current
+    /// owner of the token, who pays the stake and refunds the previous depositor
+    TakeOver {
+        token: Token,
+    },
+    /// Challenges the owner of `opponent_token` to a wager, escrowing `amount`
+    Challenge {
+        token: Token,
+        opponent_token: Token,
+        currency: CurrencyId,
+        against_currency: CurrencyId,
+        amount: Uint128,
+        expiry: u64,
+    },
+    /// Accepts the challenge made by `token` against a token owned by the sender
+    AcceptChallenge {
+        token: Token,
+    },
+    /// Voids a wager left unsettled past the grace period, or whose disputed result was left
+    /// unresolved for the grace period after the dispute window, returning the dispute bond
+    ClaimRefund {
+        token: Token,
+    },
+    /// Removes up to `limit` expired matchmaking items and refunds their stakes
+    PruneMatchmaking {
+        limit: Option<u32>,
+    },
+    /// Disputes a proposed result, requires `dispute_bond`
+    DisputeResult {
+        token: Token,
+    },
+}
+
+/// Message carried by `SendNft`, the NFT is held by the contract until the
+/// wager is cancelled or settled and the stake is taken from `Deposit`
+#[cw_serde]
+pub enum ReceiveMsg {
+    Wager {
+        currency: WagerCurrency,
+        against_currencies: Vec<CurrencyId>,
+        expiry: u64,
+        amount: Uint128,
+        settlement_mode: Option<SettlementMode>,
+        direction: Option<Direction>,
+        wager_type: Option<WagerType>,
+        against_amount: Option<Uint128>,
+        scoring_mode: Option<ScoringMode>,
+    },
+    /// Stakes the NFT itself, the winner takes the token of the loser and
+    /// `token_wager_fee` is taken from `Deposit`
+    WagerToken {
+        currency: WagerCurrency,
+        against_currencies: Vec<CurrencyId>,
+        expiry: u64,
+        settlement_mode: Option<SettlementMode>,
+        direction: Option<Direction>,
+        wager_type: Option<WagerType>,
+        scoring_mode: Option<ScoringMode>,
+    },
+}
+
+/// Message carried by a cw20 `Send`, the sent tokens are the stake
+#[cw_serde]
+pub enum Cw20HookMsg {
+    Wager {
+        token: Token,
+        currency: WagerCurrency,
+        against_currencies: Vec<CurrencyId>,
+        expiry: u64,
+        settlement_mode: Option<SettlementMode>,
+        direction: Option<Direction>,
+        wager_type: Option<WagerType>,
+        against_amount: Option<Uint128>,
+        scoring_mode: Option<ScoringMode>,
+    },
+    TakeOver {
+        token: Token,
+    },
 }
 
 #[cw_serde]
@@ -55,6 +304,22 @@ pub enum QueryMsg {
     Matchmaking {},
     #[returns(TokenStatusResponse)]
     TokenStatus { token: Token },
+    #[returns(ChallengesResponse)]
+    Challenges {
+        opponent_token: Option<Token>,
+        start_after: Option<Token>,
+        limit: Option<u32>,
+    },
+    #[returns(ProposedResultResponse)]
+    ProposedResult { wager_key: (Token, Token) },
+    #[returns(DepositResponse)]
+    Deposit { address: String },
+    #[returns(CurrenciesResponse)]
+    Currencies {},
+    #[returns(DenomsResponse)]
+    Denoms {},
+    #[returns(CollectionsResponse)]
+    Collections {},
     #[returns(ConfigResponse)]
     Config {},
 }
@@ -76,11 +341,61 @@ pub struct MatchmakingResponse {
     pub matchmaking: Vec<MatchmakingItemExport>,
 }
 
+#[cw_serde]
+pub struct ChallengesResponse {
+    pub challenges: Vec<ChallengeExport>,
+}
+
 #[cw_serde]
 pub struct TokenStatusResponse {
     pub token_status: TokenStatus,
 }
 
+#[cw_serde]
+pub struct ProposedResultResponse {
+    pub result: ProposedResult,
+}
+
+#[cw_serde]
+pub struct DepositResponse {
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct CurrencyResponse {
+    pub id: CurrencyId,
+    pub enabled: bool,
+    pub feed_id: String,
+    pub volatility: Option<Decimal>,
+}
+
+#[cw_serde]
+pub struct CurrenciesResponse {
+    pub currencies: Vec<CurrencyResponse>,
+}
+
+#[cw_serde]
+pub struct DenomResponse {
+    pub denom: Denom,
+    pub amounts: Vec<Uint128>,
+}
+
+#[cw_serde]
+pub struct DenomsResponse {
+    pub denoms: Vec<DenomResponse>,
+}
+
+#[cw_serde]
+pub struct CollectionResponse {
+    pub address: Addr,
+    pub config: CollectionConfig,
+}
+
+#[cw_serde]
+pub struct CollectionsResponse {
+    pub collections: Vec<CollectionResponse>,
+}
+
 #[cw_serde]
 pub struct ConfigResponse {
     pub config: Config,
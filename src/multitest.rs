@@ -1,15 +1,29 @@
+use std::cell::Cell;
+use std::marker::PhantomData;
 use std::str::FromStr;
 
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
 #[cfg(test)]
 use cosmwasm_std::{coin, Timestamp, Uint128};
-use cosmwasm_std::{coins, Addr, Coin, Decimal};
-use cw721::Cw721ExecuteMsg;
+use cosmwasm_std::{
+    coins, to_binary, Addr, Binary, Coin, ContractResult, Decimal, Deps, DepsMut, Empty, Env,
+    MessageInfo, Order, OwnedDeps, Record, StdError, StdResult, Storage, SystemResult,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, Denom};
+use cw721::{Cw721ExecuteMsg, Cw721QueryMsg, OwnerOfResponse};
+use cw_storage_plus::Map;
+use k256::ecdsa::signature::hazmat::PrehashSigner;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
 use sg2::tests::mock_collection_params_1;
+use sha2::{Digest, Sha256};
 
 use cw_multi_test::{BankSudo, Contract, ContractWrapper, Executor, SudoMsg as CwSudoMsg};
 use sg_multi_test::StargazeApp;
 
-use sg_std::{StargazeMsgWrapper, GENESIS_MINT_START_TIME, NATIVE_DENOM};
+use sg_std::{Response, StargazeMsgWrapper, GENESIS_MINT_START_TIME, NATIVE_DENOM};
 use vending_factory::msg::{
     ExecuteMsg as VendingFactoryExecuteMsg, VendingMinterCreateMsg, VendingMinterInitMsgExtension,
 };
@@ -18,7 +32,16 @@ use vending_factory::{helpers::FactoryContract, msg::InstantiateMsg as FactoryIn
 
 use crate::config::ParamInfo;
 // use crate::msg::WagersResponse;
-use crate::msg::{ConfigResponse, ExecuteMsg, QueryMsg};
+use crate::msg::{
+    ChallengesResponse, CollectionsResponse, ConfigResponse, CurrenciesResponse, CurrencyResponse,
+    Cw20HookMsg, DenomResponse, DenomsResponse, DepositResponse, ExecuteMsg, MatchmakingResponse,
+    OracleInfo, PriceAttestation, PriceReport, ProposedResultResponse, QueryMsg, ReceiveMsg,
+    ReportSignature, TokenStatusResponse, WagerCurrency, WagerResponse, WagerResult,
+};
+use crate::state::{
+    CurrencyId, Direction, OracleQueryFormat, PayoutPolicy, Price, ScoringMode, SettlementMode,
+    Signer, SignerKeyType, StakeType, Token, TokenStatus, WagerType,
+};
 use crate::ContractError;
 
 const GOVERNANCE: &str = "governance";
@@ -27,8 +50,15 @@ const CREATOR: &str = "creator";
 const TOKEN1_ID: u32 = 45;
 const TOKEN2_ID: u32 = 85;
 
+// Token of the collection created by `setup_contracts`
+fn token(token_id: u32) -> Token {
+    (Addr::unchecked("contract2"), token_id as u64)
+}
+
 const SENDER: &str = "sender";
 const PEER: &str = "peer";
+const KEEPER: &str = "keeper";
+const ARBITER: &str = "arbiter";
 
 fn custom_mock_app() -> StargazeApp {
     StargazeApp::default()
@@ -115,6 +145,119 @@ pub fn contract_vending_minter() -> Box<dyn Contract<StargazeMsgWrapper>> {
     Box::new(contract)
 }
 
+#[cw_serde]
+pub enum MockOracleExecuteMsg {
+    SetPrice {
+        currency: CurrencyId,
+        price: Decimal,
+    },
+}
+
+// Answers both `OracleQueryFormat::Currency` and `OracleQueryFormat::Symbol` queries
+#[cw_serde]
+pub enum MockOracleQueryMsg {
+    Price {
+        currency: Option<CurrencyId>,
+        symbol: Option<String>,
+    },
+}
+
+const MOCK_PRICES: Map<String, Price> = Map::new("mock_prices");
+
+fn mock_oracle_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn mock_oracle_execute(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: MockOracleExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        MockOracleExecuteMsg::SetPrice { currency, price } => {
+            MOCK_PRICES.save(
+                deps.storage,
+                currency.to_string(),
+                &Price {
+                    price,
+                    updated_at: env.block.time,
+                },
+            )?;
+            Ok(Response::new())
+        }
+    }
+}
+
+fn mock_oracle_query(deps: Deps, _env: Env, msg: MockOracleQueryMsg) -> StdResult<Binary> {
+    match msg {
+        MockOracleQueryMsg::Price { currency, symbol } => {
+            let key = currency
+                .map(|c| c.to_string())
+                .or_else(|| symbol.map(|s| s.to_lowercase()))
+                .unwrap_or_default();
+            to_binary(&MOCK_PRICES.load(deps.storage, key)?)
+        }
+    }
+}
+
+pub fn contract_cw20() -> Box<dyn Contract<StargazeMsgWrapper>> {
+    let contract = ContractWrapper::new_with_empty(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Plain cw721 collection, used as a collection besides the sg721 one
+type MockCollection<'a> = cw721_base::Cw721Contract<'a, cw721_base::Extension, Empty>;
+
+fn mock_collection_instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: cw721_base::InstantiateMsg,
+) -> StdResult<cosmwasm_std::Response> {
+    MockCollection::default().instantiate(deps, env, info, msg)
+}
+
+fn mock_collection_execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: cw721_base::ExecuteMsg<cw721_base::Extension>,
+) -> Result<cosmwasm_std::Response, cw721_base::ContractError> {
+    MockCollection::default().execute(deps, env, info, msg)
+}
+
+fn mock_collection_query(deps: Deps, env: Env, msg: cw721_base::QueryMsg) -> StdResult<Binary> {
+    MockCollection::default().query(deps, env, msg)
+}
+
+pub fn contract_mock_collection() -> Box<dyn Contract<StargazeMsgWrapper>> {
+    let contract = ContractWrapper::new_with_empty(
+        mock_collection_execute,
+        mock_collection_instantiate,
+        mock_collection_query,
+    );
+    Box::new(contract)
+}
+
+pub fn contract_mock_oracle() -> Box<dyn Contract<StargazeMsgWrapper>> {
+    let contract = ContractWrapper::new(
+        mock_oracle_execute,
+        mock_oracle_instantiate,
+        mock_oracle_query,
+    );
+    Box::new(contract)
+}
+
 fn setup_block_time(router: &mut StargazeApp, seconds: u64) {
     let mut block = router.block_info();
     block.time = Timestamp::from_seconds(seconds);
@@ -176,20 +319,7 @@ fn setup_contracts(
 
     // Instantiate wager contract
     let cw_wager_id = router.store_code(contract_cw_wager());
-    let msg = crate::msg::InstantiateMsg {
-        max_currencies: 3,
-        amounts: vec![
-            Uint128::from(100_000_000u128),
-            Uint128::from(250_000_000u128),
-            Uint128::from(500_000_000u128),
-        ],
-        expiries: vec![15, 30, 60],
-        fee_bps: 400,      // 4%
-        fairburn_bps: 100, // 1%
-        fee_address: CREATOR.into(),
-        collection_address: Addr::unchecked("contract2").to_string(),
-        matchmaking_expiry: 60,
-    };
+    let msg = instantiate_msg();
     let cw_wager = router
         .instantiate_contract(
             cw_wager_id,
@@ -204,6 +334,44 @@ fn setup_contracts(
     Ok((cw_wager, Addr::unchecked("contract2")))
 }
 
+// Instantiate message of the wager contract used by the tests
+fn instantiate_msg() -> crate::msg::InstantiateMsg {
+    crate::msg::InstantiateMsg {
+        max_currencies: 3,
+        amounts: vec![
+            Uint128::from(100_000_000u128),
+            Uint128::from(250_000_000u128),
+            Uint128::from(500_000_000u128),
+        ],
+        expiries: vec![15, 30, 60],
+        twap_windows: vec![30],
+        fee_bps: 400,      // 4%
+        fairburn_bps: 100, // 1%
+        fee_address: CREATOR.into(),
+        collection_address: Addr::unchecked("contract2").to_string(),
+        cross_collection: false,
+        matchmaking_expiry: 60,
+        oracles: vec![],
+        min_price_sources: 1,
+        max_price_age: 300,
+        signers: vec![],
+        signature_threshold: 1,
+        attestation_window: 10,
+        keeper_reward_bps: 1000, // 10% of the fee
+        prune_reward_bps: 0,
+        settlement_grace_period: 3600,
+        dispute_window: 0,
+        dispute_bond: Uint128::from(10_000_000u128),
+        arbiter: None,
+        payout_policy: None,
+        token_wager_fee: Uint128::from(5_000_000u128),
+        currencies: ["atom", "stars", "btc", "eth", "osmo"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect(),
+    }
+}
+
 // Intializes accounts with balances
 fn setup_accounts(router: &mut StargazeApp) -> Result<(Addr, Addr, Addr), ContractError> {
     let sender: Addr = Addr::unchecked(SENDER);
@@ -249,6 +417,76 @@ fn setup_accounts(router: &mut StargazeApp) -> Result<(Addr, Addr, Addr), Contra
     Ok((sender, peer, creator))
 }
 
+// Mints tokens 45 & 85 and transfers them to `sender` & `peer`
+fn setup_tokens(
+    router: &mut StargazeApp,
+    creator: &Addr,
+    sender: &Addr,
+    peer: &Addr,
+    collection: &Addr,
+) {
+    // Mint nfts 45 & 85
+    let mint_msg = vending_minter::msg::ExecuteMsg::Mint {};
+    let res = router.execute_contract(
+        creator.clone(),
+        Addr::unchecked("contract1").clone(),
+        &mint_msg,
+        &[coin(MIN_MINT_PRICE, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+    let res = router.execute_contract(
+        creator.clone(),
+        Addr::unchecked("contract1").clone(),
+        &mint_msg,
+        &[coin(MIN_MINT_PRICE, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Transfer nfts to sender & peer
+    let transfer_msg = Cw721ExecuteMsg::TransferNft {
+        recipient: sender.to_string(),
+        token_id: TOKEN1_ID.to_string(),
+    };
+    let res = router.execute_contract(creator.clone(), collection.clone(), &transfer_msg, &[]);
+    assert!(res.is_ok());
+    let transfer_msg = Cw721ExecuteMsg::TransferNft {
+        recipient: peer.to_string(),
+        token_id: TOKEN2_ID.to_string(),
+    };
+    let res = router.execute_contract(creator.clone(), collection.clone(), &transfer_msg, &[]);
+    assert!(res.is_ok());
+}
+
+#[test]
+fn try_migrate() {
+    let mut deps = mock_dependencies();
+
+    // Attempt to migrate a 0.1 contract
+    // Expects: failure, its state cannot be read by this version
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:cw-wager", "0.1.0").unwrap();
+    let err = crate::contract::migrate(deps.as_mut(), mock_env(), Empty {}).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(
+            "Cannot upgrade from 0.1, instantiate a new contract"
+        ))
+    );
+
+    // Attempt to migrate a contract of the current version
+    // Expects: failure
+    cw2::set_contract_version(
+        deps.as_mut().storage,
+        "crates.io:cw-wager",
+        env!("CARGO_PKG_VERSION"),
+    )
+    .unwrap();
+    let err = crate::contract::migrate(deps.as_mut(), mock_env(), Empty {}).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("Cannot upgrade from a newer version"))
+    );
+}
+
 #[test]
 fn try_update_config() {
     let router = &mut custom_mock_app();
@@ -260,13 +498,7 @@ fn try_update_config() {
     let update_config_msg = ExecuteMsg::UpdateConfig {
         params: ParamInfo {
             max_currencies: Some(2),
-            amounts: None,
-            expiries: None,
-            fee_bps: None,
-            fairburn_bps: None,
-            fee_address: None,
-            collection_address: None,
-            matchmaking_expiry: None,
+            ..Default::default()
         },
     };
 
@@ -318,43 +550,28 @@ fn try_wager() {
         Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
     );
 
-    // Mint nfts 45 & 85
-    let mint_msg = vending_minter::msg::ExecuteMsg::Mint {};
-    let res = router.execute_contract(
-        creator.clone(),
-        Addr::unchecked("contract1").clone(),
-        &mint_msg,
-        &[coin(MIN_MINT_PRICE, NATIVE_DENOM)],
-    );
-    assert!(res.is_ok());
-    let res = router.execute_contract(
-        creator.clone(),
-        Addr::unchecked("contract1").clone(),
-        &mint_msg,
-        &[coin(MIN_MINT_PRICE, NATIVE_DENOM)],
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
     );
-    assert!(res.is_ok());
-
-    // Transfer nfts to sender & peer
-    let transfer_msg = Cw721ExecuteMsg::TransferNft {
-        recipient: sender.to_string(),
-        token_id: TOKEN1_ID.to_string(),
-    };
-    let res = router.execute_contract(creator.clone(), collection.clone(), &transfer_msg, &[]);
-    assert!(res.is_ok());
-    let transfer_msg = Cw721ExecuteMsg::TransferNft {
-        recipient: peer.to_string(),
-        token_id: TOKEN2_ID.to_string(),
-    };
-    let res = router.execute_contract(creator.clone(), collection.clone(), &transfer_msg, &[]);
-    assert!(res.is_ok());
 
     // Submit a wager for matchmaking
     let wager_msg = ExecuteMsg::Wager {
-        token: TOKEN1_ID as u64,
-        currency: crate::state::Currency::Atom,
-        against_currencies: vec![crate::state::Currency::Stars],
+        token: token(TOKEN1_ID),
+        currency: "atom".into(),
+        against_currencies: vec!["stars".to_string()],
         expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
     };
 
     // Attempt to submit a wager from `sender`
@@ -369,10 +586,15 @@ fn try_wager() {
 
     // Submit a wager for matchmaking to `sender` from `peer`
     let wager_msg = ExecuteMsg::Wager {
-        token: TOKEN2_ID as u64,
-        currency: crate::state::Currency::Stars,
-        against_currencies: vec![crate::state::Currency::Atom],
+        token: token(TOKEN2_ID),
+        currency: "stars".into(),
+        against_currencies: vec!["atom".to_string()],
         expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
     };
 
     // Attempt to submit a wager from `peer`
@@ -385,24 +607,24 @@ fn try_wager() {
     );
     assert!(res.is_ok());
 
-    // let query_msg = QueryMsg::Wagers {};
-    // let res: WagersResponse = router
-    //     .wrap()
-    //     .query_wasm_smart(wager_contract.clone(), &query_msg)
-    //     .unwrap();
+    // The opening prices are recorded on the wager
+    let query_msg = QueryMsg::Wager {
+        token: token(TOKEN1_ID),
+    };
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(
+        res.wager.wagers.0.opening_prices,
+        vec![Decimal::from_str("100.0").unwrap()]
+    );
 
     // Attempt to set the wager as won, even thought it has not expired yet
     // Expects: failure
     let set_winner_msg = ExecuteMsg::SetWinner {
-        wager_key: (TOKEN1_ID as u64, TOKEN2_ID as u64),
-        prev_prices: (
-            Decimal::from_str("100.0").unwrap(),
-            Decimal::from_str("100.0").unwrap(),
-        ),
-        current_prices: (
-            Decimal::from_str("110.0").unwrap(),
-            Decimal::from_str("150.5").unwrap(),
-        ), // 2nd wins, because of bigger increase
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: None,
     };
     let err = router
         .execute_contract(
@@ -423,6 +645,32 @@ fn try_wager() {
         Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 1000,
     );
 
+    // 2nd wins, because of bigger increase
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "110.0"),
+            ("stars".to_string(), "150.5"),
+        ],
+    );
+
+    // Attempt to set the wager as won from a non-admin address without an oracle
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &set_winner_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
     // Attempt to set the wager as won
     // Expects: success
     let res = router.execute_contract(
@@ -433,3 +681,4288 @@ fn try_wager() {
     );
     assert!(res.is_ok());
 }
+
+// Matches `sender`'s token 45 (Atom) against `peer`'s token 85 (Stars)
+fn setup_wager(router: &mut StargazeApp, wager_contract: &Addr, sender: &Addr, peer: &Addr) {
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN1_ID),
+        currency: "atom".into(),
+        against_currencies: vec!["stars".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN2_ID),
+        currency: "stars".into(),
+        against_currencies: vec!["atom".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+}
+
+fn update_prices(
+    router: &mut StargazeApp,
+    wager_contract: &Addr,
+    creator: &Addr,
+    prices: &[(CurrencyId, &str)],
+) {
+    let msg = ExecuteMsg::UpdatePrices {
+        prices: prices
+            .iter()
+            .map(|(currency, price)| (currency.clone(), Decimal::from_str(price).unwrap()))
+            .collect(),
+    };
+    let res = router.execute_contract(creator.clone(), wager_contract.clone(), &msg, &[]);
+    assert!(res.is_ok());
+}
+
+// Instantiates `count` mock oracles and sets them as the price sources
+fn setup_oracles(
+    router: &mut StargazeApp,
+    creator: &Addr,
+    wager_contract: &Addr,
+    count: usize,
+    min_price_sources: u8,
+) -> Vec<Addr> {
+    let oracle_id = router.store_code(contract_mock_oracle());
+    let oracles = (0..count)
+        .map(|_| {
+            router
+                .instantiate_contract(oracle_id, creator.clone(), &Empty {}, &[], "oracle", None)
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        params: ParamInfo {
+            oracles: Some(
+                oracles
+                    .iter()
+                    .map(|oracle| OracleInfo {
+                        address: oracle.to_string(),
+                        query_format: OracleQueryFormat::Symbol,
+                    })
+                    .collect(),
+            ),
+            min_price_sources: Some(min_price_sources),
+            ..Default::default()
+        },
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &update_config_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    oracles
+}
+
+fn set_oracle_price(
+    router: &mut StargazeApp,
+    oracle: &Addr,
+    creator: &Addr,
+    currency: CurrencyId,
+    price: &str,
+) {
+    let msg = MockOracleExecuteMsg::SetPrice {
+        currency,
+        price: Decimal::from_str(price).unwrap(),
+    };
+    let res = router.execute_contract(creator.clone(), oracle.clone(), &msg, &[]);
+    assert!(res.is_ok());
+}
+
+#[test]
+fn try_oracle_settlement() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+    let oracle = setup_oracles(router, &creator, &wager_contract, 1, 1).remove(0);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "100.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "100.0");
+    setup_wager(router, &wager_contract, &sender, &peer);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "110.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "150.5");
+
+    // The opening prices recorded on the wager do not follow the feed
+    let query_msg = QueryMsg::Wager {
+        token: token(TOKEN1_ID),
+    };
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(
+        res.wager.wagers.0.opening_prices,
+        vec![Decimal::from_str("100.0").unwrap()]
+    );
+    assert_eq!(
+        res.wager.wagers.1.opening_prices,
+        vec![Decimal::from_str("100.0").unwrap()]
+    );
+
+    // Attempt to set the winner using the oracle prices from a keeper
+    // Expects: success, `peer` wins because Stars increased more
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: None,
+    };
+    let res = router.execute_contract(
+        Addr::unchecked(KEEPER),
+        wager_contract.clone(),
+        &set_winner_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // 200 STARS pot minus 4% fee and 1% fair burn
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_090_000_000u128));
+
+    // 10% of the 8 STARS fee goes to the keeper
+    let balance = router.wrap().query_balance(KEEPER, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(800_000u128));
+}
+
+#[test]
+fn try_keeper_settlement() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+    let oracle = setup_oracles(router, &creator, &wager_contract, 1, 1).remove(0);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "100.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "100.0");
+    setup_wager(router, &wager_contract, &sender, &peer);
+
+    // Attempt to settle the wager from a keeper before it expires
+    // Expects: failure
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: None,
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &set_winner_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::WagerActive {}
+    );
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "110.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "150.5");
+
+    // Record the closing prices from a keeper
+    // Expects: success
+    let res = router.execute_contract(
+        Addr::unchecked(KEEPER),
+        wager_contract.clone(),
+        &ExecuteMsg::RecordPrices {
+            currencies: vec!["atom".to_string(), "stars".to_string()],
+        },
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // The oracle moves on before the wager is settled, Atom would now win
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 200,
+    );
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "200.0");
+
+    // Attempt to settle the expired wager from a keeper
+    // Expects: success, the wager is settled on the recorded prices and the keeper is paid
+    // `keeper_reward_bps` of the 8 STARS fee
+    let res = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &set_winner_msg,
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "keeper" && a.value == KEEPER)));
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "keeper_reward" && a.value == "800000")));
+
+    let balance = router.wrap().query_balance(KEEPER, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(800_000u128));
+
+    // 200 STARS pot minus 4% fee and 1% fair burn
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_090_000_000u128));
+}
+
+#[test]
+fn try_set_winners() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+    let oracle = setup_oracles(router, &creator, &wager_contract, 1, 1).remove(0);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "100.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "100.0");
+    setup_wager(router, &wager_contract, &sender, &peer);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "110.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "150.5");
+
+    // Attempt to settle an expired wager alongside one that does not exist
+    // Expects: success, the missing wager is reported as failed
+    let set_winners_msg = ExecuteMsg::SetWinners {
+        results: vec![
+            WagerResult {
+                wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+                attestations: None,
+            },
+            WagerResult {
+                wager_key: (token(1), token(2)),
+                attestations: None,
+            },
+        ],
+    };
+    let res = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &set_winners_msg,
+            &[],
+        )
+        .unwrap();
+
+    let attributes = res
+        .events
+        .iter()
+        .filter(|e| e.ty == "wasm")
+        .flat_map(|e| e.attributes.iter())
+        .collect::<Vec<_>>();
+    assert!(attributes
+        .iter()
+        .any(|a| a.key == "settled" && a.value == "contract2:45-contract2:85"));
+    assert!(attributes
+        .iter()
+        .any(|a| a.key == "winner" && a.value == peer.as_str()));
+    assert!(attributes
+        .iter()
+        .any(|a| a.key == "failed" && a.value.starts_with("contract2:1-contract2:2")));
+
+    // 200 STARS pot minus 4% fee and 1% fair burn
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_090_000_000u128));
+
+    let balance = router.wrap().query_balance(KEEPER, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(800_000u128));
+
+    // Attempt to settle the same wager again
+    // Expects: success, the wager is reported as failed
+    let res = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract,
+            &set_winners_msg,
+            &[],
+        )
+        .unwrap();
+    assert!(!res
+        .events
+        .iter()
+        .flat_map(|e| e.attributes.iter())
+        .any(|a| a.key == "settled"));
+}
+
+#[test]
+fn try_challenge() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    // Attempt to challenge `peer` with a stake that does not match the amount
+    // Expects: failure
+    let challenge_msg = ExecuteMsg::Challenge {
+        token: token(TOKEN1_ID),
+        opponent_token: token(TOKEN2_ID),
+        currency: "atom".into(),
+        against_currency: "stars".into(),
+        amount: Uint128::from(250_000_000u128),
+        expiry: 60,
+    };
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &challenge_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "amount".into()
+        }
+    );
+
+    // Attempt to challenge `peer`
+    // Expects: success
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &challenge_msg,
+        &[coin(250_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    let query_msg = QueryMsg::TokenStatus {
+        token: token(TOKEN1_ID),
+    };
+    let res: TokenStatusResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert!(matches!(res.token_status, TokenStatus::Challenge(_)));
+
+    let query_msg = QueryMsg::Challenges {
+        opponent_token: Some(token(TOKEN2_ID)),
+        start_after: None,
+        limit: None,
+    };
+    let res: ChallengesResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.challenges.len(), 1);
+    assert_eq!(res.challenges[0].token.token_id, TOKEN1_ID as u64);
+
+    // Attempt to accept the challenge from the challenger
+    // Expects: failure
+    let accept_msg = ExecuteMsg::AcceptChallenge {
+        token: token(TOKEN1_ID),
+    };
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &accept_msg,
+            &[coin(250_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Attempt to accept the challenge with the wrong stake
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &accept_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "amount".into()
+        }
+    );
+
+    // Attempt to accept the challenge once a currency of it is disabled
+    // Expects: failure
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::DisableCurrency {
+            id: "stars".to_string(),
+        },
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let err = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &accept_msg,
+            &[coin(250_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CurrencyDisabled {
+            currency: "stars".to_string()
+        }
+    );
+
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::AddCurrency {
+            id: "stars".to_string(),
+            feed_id: None,
+        },
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to accept the challenge once the collection is removed
+    // Expects: failure
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::RemoveCollection {
+            address: collection.to_string(),
+        },
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let err = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &accept_msg,
+            &[coin(250_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnknownCollection {
+            collection: collection.to_string()
+        }
+    );
+
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::SetCollection {
+            address: collection.to_string(),
+            amounts: None,
+            expiries: None,
+            fee_bps: None,
+            matchmaking_expiry: None,
+        },
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to accept the challenge from `peer`
+    // Expects: success, a wager is created
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &accept_msg,
+        &[coin(250_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    let query_msg = QueryMsg::Wager {
+        token: token(TOKEN2_ID),
+    };
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.wager.wagers.0.stake, Uint128::from(250_000_000u128));
+    assert_eq!(res.wager.wagers.0.token.token_id, TOKEN1_ID as u64);
+    assert_eq!(
+        res.wager.wagers.0.basket,
+        vec![("atom".to_string(), Decimal::one())]
+    );
+    assert_eq!(
+        res.wager.wagers.1.basket,
+        vec![("stars".to_string(), Decimal::one())]
+    );
+
+    let query_msg = QueryMsg::Challenges {
+        opponent_token: None,
+        start_after: None,
+        limit: None,
+    };
+    let res: ChallengesResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract, &query_msg)
+        .unwrap();
+    assert!(res.challenges.is_empty());
+}
+
+#[test]
+fn try_twap_settlement() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    // Attempt to wager with a TWAP window that is not allowed
+    // Expects: failure
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN1_ID),
+        currency: "atom".into(),
+        against_currencies: vec!["stars".to_string()],
+        expiry: 60,
+        settlement_mode: Some(SettlementMode::Twap { window: 45 }),
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &wager_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "settlement_mode".into()
+        }
+    );
+
+    // Attempt to wager with a TWAP window on a currency whose prices are not pushed
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::Wager {
+                token: token(TOKEN1_ID),
+                currency: "btc".into(),
+                against_currencies: vec!["stars".to_string()],
+                expiry: 60,
+                settlement_mode: Some(SettlementMode::Twap { window: 30 }),
+                direction: None,
+                wager_type: None,
+                against_amount: None,
+                scoring_mode: None,
+            },
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientPriceSources {
+            currency: "btc".into()
+        }
+    );
+
+    // Attempt to match a TWAP wager against a spot wager
+    // Expects: success, both tokens are left matchmaking
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN1_ID),
+        currency: "atom".into(),
+        against_currencies: vec!["stars".to_string()],
+        expiry: 60,
+        settlement_mode: Some(SettlementMode::Twap { window: 30 }),
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    let spot_wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN2_ID),
+        currency: "stars".into(),
+        against_currencies: vec!["atom".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &spot_wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    let query_msg = QueryMsg::Matchmaking {};
+    let res: MatchmakingResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.matchmaking.len(), 2);
+
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::Cancel {
+            token: token(TOKEN2_ID),
+        },
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to match both TWAP wagers
+    // Expects: success, the wager settles over the last 30 seconds
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN2_ID),
+        currency: "stars".into(),
+        against_currencies: vec!["atom".to_string()],
+        expiry: 60,
+        settlement_mode: Some(SettlementMode::Twap { window: 30 }),
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    let query_msg = QueryMsg::Wager {
+        token: token(TOKEN1_ID),
+    };
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(
+        res.wager.settlement_mode,
+        SettlementMode::Twap { window: 30 }
+    );
+
+    // Atom rises for the whole window, Stars spikes one second before expiry
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 130,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[("atom".to_string(), "110.0")],
+    );
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 159,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[("stars".to_string(), "200.0")],
+    );
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 1000,
+    );
+
+    // Attempt to set the winner
+    // Expects: success, `sender` wins with a TWAP of 110 against ~103.3
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: None,
+    };
+    let res = router.execute_contract(creator, wager_contract, &set_winner_msg, &[]);
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&sender, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_090_000_000u128));
+}
+
+// Storage that counts every read, used to compare the cost of matchmaking as the book grows
+struct CountingStorage {
+    storage: MockStorage,
+    reads: Cell<u64>,
+}
+
+impl Storage for CountingStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.reads.set(self.reads.get() + 1);
+        self.storage.get(key)
+    }
+
+    fn range<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'a> {
+        Box::new(
+            self.storage
+                .range(start, end, order)
+                .inspect(|_| self.reads.set(self.reads.get() + 1)),
+        )
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.storage.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.storage.remove(key)
+    }
+}
+
+// Count the storage reads of a `Wager` message matched against a book of `size` wagers, `size`
+// items resting in other buckets and `size` expired items in its own bucket, spread over three
+// collections that are all searched when `cross_collection` is set
+fn matchmaking_reads(size: u32, cross_collection: bool) -> u64 {
+    let mut deps = OwnedDeps {
+        storage: CountingStorage {
+            storage: MockStorage::new(),
+            reads: Cell::new(0),
+        },
+        api: MockApi::default(),
+        querier: MockQuerier::<Empty>::new(&[]),
+        custom_query_type: PhantomData,
+    };
+    // Every token is owned by `sender`
+    deps.querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(
+            to_binary(&OwnerOfResponse {
+                owner: SENDER.to_string(),
+                approvals: vec![],
+            })
+            .unwrap(),
+        ))
+    });
+
+    let mut env = mock_env();
+    let collections = ["contract2", "contract3", "contract4"].map(Addr::unchecked);
+    let admin = mock_info(CREATOR, &[]);
+    let sender = mock_info(SENDER, &coins(100_000_000, NATIVE_DENOM));
+
+    let msg = crate::msg::InstantiateMsg {
+        cross_collection,
+        ..instantiate_msg()
+    };
+    crate::contract::instantiate(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+    for collection in &collections[1..] {
+        let msg = ExecuteMsg::SetCollection {
+            address: collection.to_string(),
+            amounts: None,
+            expiries: None,
+            fee_bps: None,
+            matchmaking_expiry: None,
+        };
+        crate::contract::execute(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+    }
+
+    let mut token_ids = 0..;
+    let mut wager =
+        |deps: DepsMut, env: &Env, collection: &Addr, currencies: (&str, &str), expiry| {
+            let msg = ExecuteMsg::Wager {
+                token: (collection.clone(), token_ids.next().unwrap()),
+                currency: currencies.0.into(),
+                against_currencies: vec![currencies.1.to_string()],
+                expiry,
+                settlement_mode: None,
+                direction: None,
+                wager_type: None,
+                against_amount: None,
+                scoring_mode: None,
+            };
+            crate::contract::execute(deps, env.clone(), sender.clone(), msg).unwrap()
+        };
+
+    // Items in the bucket of the wager that expire before it is placed
+    for i in 0..size as usize {
+        wager(
+            deps.as_mut(),
+            &env,
+            &collections[i % 3],
+            ("stars", "atom"),
+            60,
+        );
+    }
+
+    env.block.time = env.block.time.plus_seconds(120);
+    let msg = ExecuteMsg::UpdatePrices {
+        prices: ["atom", "stars", "btc", "eth"]
+            .iter()
+            .map(|c| (c.to_string(), Decimal::percent(10_000)))
+            .collect(),
+    };
+    crate::contract::execute(deps.as_mut(), env.clone(), admin, msg).unwrap();
+
+    for i in 0..size as usize {
+        // Two tokens matched into a wager
+        wager(deps.as_mut(), &env, &collections[i % 3], ("btc", "eth"), 15);
+        wager(deps.as_mut(), &env, &collections[i % 3], ("eth", "btc"), 15);
+        // An item resting in another bucket
+        let expiry = [15, 30, 60][i % 3];
+        wager(
+            deps.as_mut(),
+            &env,
+            &collections[i % 3],
+            ("osmo", "btc"),
+            expiry,
+        );
+    }
+
+    wager(deps.as_mut(), &env, &collections[0], ("stars", "atom"), 60);
+
+    deps.storage.reads.set(0);
+    let res = wager(deps.as_mut(), &env, &collections[0], ("atom", "stars"), 60);
+    assert!(res
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "wager"));
+
+    deps.storage.reads.get()
+}
+
+#[test]
+fn bench_matchmaking() {
+    // Placing a wager only reads the unexpired items of the compatible bucket and the wagers
+    // of its own token, so the cost does not grow with the book
+    let reads = matchmaking_reads(10, false);
+    assert_eq!(matchmaking_reads(1_000, false), reads);
+    assert_eq!(matchmaking_reads(5_000, false), reads);
+
+    // Across collections, the cost only grows with the number of collections
+    let reads = matchmaking_reads(10, true);
+    assert_eq!(matchmaking_reads(1_000, true), reads);
+    assert_eq!(matchmaking_reads(5_000, true), reads);
+}
+
+#[test]
+fn try_prune_matchmaking() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        params: ParamInfo {
+            prune_reward_bps: Some(100), // 1% of each stake
+            ..Default::default()
+        },
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &update_config_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+
+    // Both tokens matchmake against currencies that never match
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN1_ID),
+        currency: "atom".into(),
+        against_currencies: vec!["btc".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 130,
+    );
+
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN2_ID),
+        currency: "stars".into(),
+        against_currencies: vec!["eth".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Set time past the expiry of the first item only
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 170,
+    );
+
+    // Attempt to prune the matchmaking book from a keeper
+    // Expects: success, only the expired item is removed
+    let prune_msg = ExecuteMsg::PruneMatchmaking { limit: None };
+    let res = router.execute_contract(
+        Addr::unchecked(KEEPER),
+        wager_contract.clone(),
+        &prune_msg,
+        &[],
+    );
+    res.unwrap();
+
+    let query_msg = QueryMsg::Matchmaking {};
+    let res: MatchmakingResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.matchmaking.len(), 1);
+    assert_eq!(res.matchmaking[0].token.token_id, TOKEN2_ID as u64);
+
+    // 1% of the 100 STARS stake goes to the keeper
+    let balance = router.wrap().query_balance(&sender, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(1_999_999_999_000_000u128));
+    let balance = router.wrap().query_balance(KEEPER, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(1_000_000u128));
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 1000,
+    );
+
+    // Attempt to prune with a limit of zero
+    // Expects: success, nothing is removed
+    let res = router.execute_contract(
+        Addr::unchecked(KEEPER),
+        wager_contract.clone(),
+        &ExecuteMsg::PruneMatchmaking { limit: Some(0) },
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let res: MatchmakingResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.matchmaking.len(), 1);
+
+    let res = router.execute_contract(
+        Addr::unchecked(KEEPER),
+        wager_contract.clone(),
+        &prune_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let res: MatchmakingResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract, &query_msg)
+        .unwrap();
+    assert!(res.matchmaking.is_empty());
+
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(1_999_999_999_000_000u128));
+}
+
+#[test]
+fn try_take_over() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN1_ID),
+        currency: "atom".into(),
+        against_currencies: vec!["btc".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // `sender` sells the token while it is matchmaking
+    let transfer_msg = Cw721ExecuteMsg::TransferNft {
+        recipient: peer.to_string(),
+        token_id: TOKEN1_ID.to_string(),
+    };
+    let res = router.execute_contract(sender.clone(), collection, &transfer_msg, &[]);
+    assert!(res.is_ok());
+
+    // Attempt to cancel from the new owner of the token
+    // Expects: failure, only the depositor can withdraw the stake
+    let cancel_msg = ExecuteMsg::Cancel {
+        token: token(TOKEN1_ID),
+    };
+    let err = router
+        .execute_contract(peer.clone(), wager_contract.clone(), &cancel_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Attempt to take over the position from the previous owner
+    // Expects: failure
+    let take_over_msg = ExecuteMsg::TakeOver {
+        token: token(TOKEN1_ID),
+    };
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &take_over_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Attempt to take over the position from the new owner
+    // Expects: success, `sender` gets their stake back
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &take_over_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&sender, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_000_000_000u128));
+
+    let query_msg = QueryMsg::TokenStatus {
+        token: token(TOKEN1_ID),
+    };
+    let res: TokenStatusResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    match res.token_status {
+        TokenStatus::Matchmaking(item) => assert_eq!(item.depositor, peer),
+        _ => panic!("expected matchmaking"),
+    }
+
+    // Attempt to cancel from the new depositor
+    // Expects: success
+    let res = router.execute_contract(peer.clone(), wager_contract, &cancel_msg, &[]);
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_000_000_000u128));
+}
+
+#[test]
+fn try_escrow() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    let send_nft_msg = Cw721ExecuteMsg::SendNft {
+        contract: wager_contract.to_string(),
+        token_id: TOKEN1_ID.to_string(),
+        msg: to_binary(&ReceiveMsg::Wager {
+            currency: "atom".into(),
+            against_currencies: vec!["stars".to_string()],
+            expiry: 60,
+            amount: Uint128::from(100_000_000u128),
+            settlement_mode: None,
+            direction: None,
+            wager_type: None,
+            against_amount: None,
+            scoring_mode: None,
+        })
+        .unwrap(),
+    };
+
+    // Attempt to escrow the token without a deposit
+    // Expects: failure
+    let err = router
+        .execute_contract(sender.clone(), collection.clone(), &send_nft_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientDeposit {}
+    );
+
+    // Attempt to deposit the stake and escrow the token
+    // Expects: success, the contract holds the token
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::Deposit {},
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    let res = router.execute_contract(sender.clone(), collection.clone(), &send_nft_msg, &[]);
+    assert!(res.is_ok());
+
+    let owner_query = Cw721QueryMsg::OwnerOf {
+        token_id: TOKEN1_ID.to_string(),
+        include_expired: None,
+    };
+    let res: OwnerOfResponse = router
+        .wrap()
+        .query_wasm_smart(collection.clone(), &owner_query)
+        .unwrap();
+    assert_eq!(res.owner, wager_contract.to_string());
+
+    let res: DepositResponse = router
+        .wrap()
+        .query_wasm_smart(
+            wager_contract.clone(),
+            &QueryMsg::Deposit {
+                address: sender.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.amount, Uint128::zero());
+
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN2_ID),
+        currency: "stars".into(),
+        against_currencies: vec!["atom".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "150.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    // Attempt to set the winner
+    // Expects: success, `sender` is paid and gets the token back
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: None,
+    };
+    let res = router.execute_contract(creator, wager_contract, &set_winner_msg, &[]);
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&sender, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_090_000_000u128));
+
+    let res: OwnerOfResponse = router
+        .wrap()
+        .query_wasm_smart(collection, &owner_query)
+        .unwrap();
+    assert_eq!(res.owner, sender.to_string());
+}
+
+#[test]
+fn try_payout_policy() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        params: ParamInfo {
+            payout_policy: Some(PayoutPolicy::MatchOwner),
+            dispute_window: Some(300),
+            ..Default::default()
+        },
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &update_config_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN1_ID),
+        currency: "atom".into(),
+        against_currencies: vec!["stars".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // `sender` sells the token while it is in matchmaking
+    let transfer_msg = Cw721ExecuteMsg::TransferNft {
+        recipient: creator.to_string(),
+        token_id: TOKEN1_ID.to_string(),
+    };
+    let res = router.execute_contract(sender.clone(), collection.clone(), &transfer_msg, &[]);
+    assert!(res.is_ok());
+
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN2_ID),
+        currency: "stars".into(),
+        against_currencies: vec!["atom".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // The participants are the accounts that placed both sides of the wager
+    let query_msg = QueryMsg::Wager {
+        token: token(TOKEN1_ID),
+    };
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.wager.wagers.0.participant, peer);
+    assert_eq!(res.wager.wagers.1.participant, sender);
+
+    // `peer` sells the token before the wager is settled
+    let transfer_msg = Cw721ExecuteMsg::TransferNft {
+        recipient: KEEPER.to_string(),
+        token_id: TOKEN2_ID.to_string(),
+    };
+    let res = router.execute_contract(peer.clone(), collection, &transfer_msg, &[]);
+    assert!(res.is_ok());
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "150.0"),
+        ],
+    );
+
+    // Propose the result
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: None,
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &set_winner_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to dispute the result from the current owner of the losing token
+    // Expects: failure, only the participant is paid for that side
+    let dispute_msg = ExecuteMsg::DisputeResult {
+        token: token(TOKEN1_ID),
+    };
+    let err = router
+        .execute_contract(
+            creator.clone(),
+            wager_contract.clone(),
+            &dispute_msg,
+            &[coin(10_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Attempt to dispute the result from `sender`, who placed the losing side
+    // Expects: success
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &dispute_msg,
+        &[coin(10_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to resolve the dispute with the proposed winner
+    // Expects: success, `peer` is paid as the owner when the wager was matched
+    let resolve_msg = ExecuteMsg::ResolveDispute {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        winner: Some(token(TOKEN2_ID)),
+    };
+    let res = router.execute_contract(creator, wager_contract, &resolve_msg, &[]);
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_090_000_000u128));
+    let balance = router.wrap().query_balance(KEEPER, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::zero());
+}
+
+#[test]
+fn try_claim_refund() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+    setup_wager(router, &wager_contract, &sender, &peer);
+
+    // Set time past expiry, but within the grace period
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 1000,
+    );
+
+    // Attempt to claim a refund before the grace period is over
+    // Expects: failure
+    let claim_refund_msg = ExecuteMsg::ClaimRefund {
+        token: token(TOKEN1_ID),
+    };
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &claim_refund_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::GracePeriodActive {}
+    );
+
+    // Set time past the grace period
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 5000,
+    );
+
+    // Attempt to claim a refund from an address that does not own the token
+    // Expects: failure
+    let err = router
+        .execute_contract(peer.clone(), wager_contract.clone(), &claim_refund_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Attempt to claim a refund from `sender`
+    // Expects: success, both stakes are returned
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &claim_refund_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&sender, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_000_000_000u128));
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_000_000_000u128));
+
+    let query_msg = QueryMsg::TokenStatus {
+        token: token(TOKEN2_ID),
+    };
+    let res: TokenStatusResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract, &query_msg)
+        .unwrap();
+    assert_eq!(res.token_status, TokenStatus::None);
+}
+
+#[test]
+fn try_oracle_median() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+    let oracles = setup_oracles(router, &creator, &wager_contract, 3, 2);
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        params: ParamInfo {
+            max_price_age: Some(30),
+            ..Default::default()
+        },
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &update_config_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    for oracle in oracles.iter() {
+        set_oracle_price(router, oracle, &creator, "atom".to_string(), "100.0");
+        set_oracle_price(router, oracle, &creator, "stars".to_string(), "100.0");
+    }
+    setup_wager(router, &wager_contract, &sender, &peer);
+
+    // The last oracle stops updating well before the wager expires
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 110,
+    );
+    set_oracle_price(router, &oracles[2], &creator, "atom".to_string(), "300.0");
+    set_oracle_price(router, &oracles[2], &creator, "stars".to_string(), "100.0");
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    set_oracle_price(router, &oracles[0], &creator, "atom".to_string(), "110.0");
+    set_oracle_price(router, &oracles[0], &creator, "stars".to_string(), "150.0");
+
+    // Attempt to set the winner with a single fresh price
+    // Expects: failure
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: None,
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &set_winner_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientPriceSources {
+            currency: "stars".into()
+        }
+    );
+
+    set_oracle_price(router, &oracles[1], &creator, "atom".to_string(), "130.0");
+    set_oracle_price(router, &oracles[1], &creator, "stars".to_string(), "120.0");
+
+    // Attempt to set the winner with two fresh prices
+    // Expects: success, `peer` wins because the stale Atom price is ignored
+    let res = router.execute_contract(
+        Addr::unchecked(KEEPER),
+        wager_contract.clone(),
+        &set_winner_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_090_000_000u128));
+}
+
+fn secp256k1_test_key() -> k256::ecdsa::SigningKey {
+    k256::ecdsa::SigningKey::from_bytes(&[1u8; 32]).unwrap()
+}
+
+fn ed25519_test_key() -> ed25519_zebra::SigningKey {
+    ed25519_zebra::SigningKey::from([2u8; 32])
+}
+
+fn test_signers() -> Vec<Signer> {
+    vec![
+        Signer {
+            key_type: SignerKeyType::Secp256k1,
+            pubkey: Binary::from(
+                secp256k1_test_key()
+                    .verifying_key()
+                    .to_encoded_point(true)
+                    .as_bytes(),
+            ),
+        },
+        Signer {
+            key_type: SignerKeyType::Ed25519,
+            pubkey: Binary::from(
+                <[u8; 32]>::from(ed25519_zebra::VerificationKey::from(&ed25519_test_key()))
+                    .to_vec(),
+            ),
+        },
+    ]
+}
+
+// Signs a price report with the test keys, `signer_count` picks how many of them sign
+fn attest_price(
+    currency: CurrencyId,
+    price: &str,
+    timestamp: Timestamp,
+    signer_count: usize,
+) -> PriceAttestation {
+    let report = PriceReport {
+        currency,
+        price: Decimal::from_str(price).unwrap(),
+        timestamp,
+    };
+    let hash = Sha256::digest(to_binary(&report).unwrap().as_slice());
+
+    let secp256k1_signature: k256::ecdsa::Signature =
+        secp256k1_test_key().sign_prehash(&hash).unwrap();
+    let ed25519_signature = ed25519_test_key().sign(&hash);
+
+    let signers = test_signers();
+    let signatures = vec![
+        ReportSignature {
+            pubkey: signers[0].pubkey.clone(),
+            signature: Binary::from(secp256k1_signature.as_ref()),
+        },
+        ReportSignature {
+            pubkey: signers[1].pubkey.clone(),
+            signature: Binary::from(<[u8; 64]>::from(ed25519_signature).to_vec()),
+        },
+    ];
+
+    PriceAttestation {
+        report,
+        signatures: signatures.into_iter().take(signer_count).collect(),
+    }
+}
+
+#[test]
+fn try_signed_attestations() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        params: ParamInfo {
+            signers: Some(test_signers()),
+            signature_threshold: Some(2),
+            ..Default::default()
+        },
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &update_config_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+    setup_wager(router, &wager_contract, &sender, &peer);
+
+    let settled_at = Timestamp::from_nanos(GENESIS_MINT_START_TIME).plus_seconds(1000);
+    setup_block_time(router, settled_at.seconds());
+
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(
+            wager_contract.clone(),
+            &QueryMsg::Wager {
+                token: token(TOKEN1_ID),
+            },
+        )
+        .unwrap();
+    let expires_at = res.wager.expires_at;
+
+    // Attempt to relay prices signed after the wager expired
+    // Expects: failure
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: Some(vec![
+            attest_price("atom".to_string(), "150.5", settled_at, 2),
+            attest_price("stars".to_string(), "110.0", settled_at, 2),
+        ]),
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &set_winner_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAttestation {
+            currency: "stars".into()
+        }
+    );
+
+    // Attempt to relay prices signed by fewer signers than the threshold
+    // Expects: failure
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: Some(vec![
+            attest_price("atom".to_string(), "150.5", expires_at, 1),
+            attest_price("stars".to_string(), "110.0", expires_at, 1),
+        ]),
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &set_winner_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAttestation {
+            currency: "stars".into()
+        }
+    );
+
+    // Attempt to settle from the price feed with empty or partial attestations
+    // Expects: failure
+    for attestations in [
+        vec![],
+        vec![attest_price("atom".to_string(), "150.5", expires_at, 2)],
+    ] {
+        let set_winner_msg = ExecuteMsg::SetWinner {
+            wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+            attestations: Some(attestations),
+        };
+        let err = router
+            .execute_contract(
+                Addr::unchecked(KEEPER),
+                wager_contract.clone(),
+                &set_winner_msg,
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::MissingAttestation {
+                currency: "stars".into()
+            }
+        );
+    }
+
+    // Attempt to relay prices signed before the attestation window
+    // Expects: failure
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: Some(vec![
+            attest_price("atom".to_string(), "150.5", expires_at.minus_seconds(60), 2),
+            attest_price(
+                "stars".to_string(),
+                "110.0",
+                expires_at.minus_seconds(60),
+                2,
+            ),
+        ]),
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &set_winner_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientPriceSources {
+            currency: "stars".into()
+        }
+    );
+
+    // Attempt to relay prices while oracles are configured
+    // Expects: failure
+    setup_oracles(router, &creator, &wager_contract, 1, 1);
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: Some(vec![
+            attest_price("atom".to_string(), "150.5", expires_at, 2),
+            attest_price("stars".to_string(), "110.0", expires_at, 2),
+        ]),
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &set_winner_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "attestations".into()
+        }
+    );
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        params: ParamInfo {
+            oracles: Some(vec![]),
+            ..Default::default()
+        },
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &update_config_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to relay prices signed by both signers
+    // Expects: success, `sender` wins because Atom increased more
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: Some(vec![
+            attest_price("atom".to_string(), "150.5", expires_at, 2),
+            attest_price("stars".to_string(), "110.0", expires_at, 2),
+        ]),
+    };
+    let res = router.execute_contract(
+        Addr::unchecked(KEEPER),
+        wager_contract.clone(),
+        &set_winner_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&sender, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_090_000_000u128));
+}
+
+#[test]
+fn try_dispute_result() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        params: ParamInfo {
+            dispute_window: Some(300),
+            arbiter: Some(ARBITER.into()),
+            ..Default::default()
+        },
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &update_config_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+    setup_wager(router, &wager_contract, &sender, &peer);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "110.0"),
+            ("stars".to_string(), "150.5"),
+        ],
+    );
+
+    // Attempt to set the winner with a dispute window
+    // Expects: success, the result is only proposed
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: None,
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &set_winner_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let query_msg = QueryMsg::ProposedResult {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+    };
+    let res: ProposedResultResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.result.winner, Some(token(TOKEN2_ID)));
+
+    // Attempt to finalize the result during the dispute window
+    // Expects: failure
+    let finalize_msg = ExecuteMsg::FinalizeResult {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &finalize_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DisputeWindowActive {}
+    );
+
+    // Attempt to dispute the result from `sender` with the bond
+    // Expects: success
+    let dispute_msg = ExecuteMsg::DisputeResult {
+        token: token(TOKEN1_ID),
+    };
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &dispute_msg,
+        &[coin(10_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to finalize the disputed result after the dispute window
+    // Expects: failure
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 2000,
+    );
+    let err = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &finalize_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ResultDisputed {}
+    );
+
+    // Attempt to resolve the dispute from a non-arbiter address
+    // Expects: failure
+    let resolve_msg = ExecuteMsg::ResolveDispute {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        winner: Some(token(TOKEN1_ID)),
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked(KEEPER),
+            wager_contract.clone(),
+            &resolve_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Attempt to overturn the result from the arbiter
+    // Expects: success, `sender` is paid out and gets the bond back
+    let res = router.execute_contract(
+        Addr::unchecked(ARBITER),
+        wager_contract.clone(),
+        &resolve_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&sender, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_090_000_000u128));
+}
+
+#[test]
+fn try_dispute_deadline() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        params: ParamInfo {
+            dispute_window: Some(300),
+            arbiter: Some(ARBITER.into()),
+            ..Default::default()
+        },
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &update_config_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+    setup_wager(router, &wager_contract, &sender, &peer);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "110.0"),
+            ("stars".to_string(), "150.5"),
+        ],
+    );
+
+    // Attempt to set the winner with a dispute window
+    // Expects: success, the result is only proposed
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        attestations: None,
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &set_winner_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let query_msg = QueryMsg::ProposedResult {
+        wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+    };
+    let res: ProposedResultResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.result.winner, Some(token(TOKEN2_ID)));
+
+    // Attempt to dispute the result from `sender` with the bond
+    // Expects: success
+    let dispute_msg = ExecuteMsg::DisputeResult {
+        token: token(TOKEN1_ID),
+    };
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &dispute_msg,
+        &[coin(10_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to claim a refund while the dispute can still be resolved
+    // Expects: failure
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 3800,
+    );
+    let claim_msg = ExecuteMsg::ClaimRefund {
+        token: token(TOKEN1_ID),
+    };
+    let err = router
+        .execute_contract(sender.clone(), wager_contract.clone(), &claim_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ResultPending {}
+    );
+
+    // Attempt to claim a refund once the dispute is left unresolved past the grace period
+    // Expects: success, both stakes and the bond are returned
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160 + 300 + 3600,
+    );
+    let res = router.execute_contract(sender.clone(), wager_contract.clone(), &claim_msg, &[]);
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&sender, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_000_000_000u128));
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_000_000_000u128));
+
+    let res = router.wrap().query_wasm_smart::<ProposedResultResponse>(
+        wager_contract,
+        &QueryMsg::ProposedResult {
+            wager_key: (token(TOKEN1_ID), token(TOKEN2_ID)),
+        },
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn try_currency_registry() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+    let oracle = setup_oracles(router, &creator, &wager_contract, 1, 1).remove(0);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+
+    // Attempt to disable a currency from a non-admin address
+    // Expects: failure
+    let disable_msg = ExecuteMsg::DisableCurrency {
+        id: "btc".to_string(),
+    };
+    let err = router
+        .execute_contract(sender.clone(), wager_contract.clone(), &disable_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Disable `btc` from the admin
+    // Expects: success
+    let res = router.execute_contract(creator.clone(), wager_contract.clone(), &disable_msg, &[]);
+    assert!(res.is_ok());
+
+    // Attempt to wager against a disabled currency
+    // Expects: failure
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN1_ID),
+        currency: "atom".into(),
+        against_currencies: vec!["btc".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &wager_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CurrencyDisabled {
+            currency: "btc".to_string()
+        }
+    );
+
+    // Attempt to wager on a currency missing from the registry
+    // Expects: failure
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN1_ID),
+        currency: "doge".into(),
+        against_currencies: vec!["stars".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &wager_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnknownCurrency {
+            currency: "doge".to_string()
+        }
+    );
+
+    // Add `doge` with a custom oracle feed id
+    // Expects: success
+    let add_msg = ExecuteMsg::AddCurrency {
+        id: "doge".to_string(),
+        feed_id: Some("dogecoin".to_string()),
+    };
+    let res = router.execute_contract(creator.clone(), wager_contract.clone(), &add_msg, &[]);
+    assert!(res.is_ok());
+
+    let res: CurrenciesResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &QueryMsg::Currencies {})
+        .unwrap();
+    assert_eq!(
+        res.currencies
+            .iter()
+            .find(|c| c.id == "btc")
+            .map(|c| c.enabled),
+        Some(false)
+    );
+    assert_eq!(
+        res.currencies.iter().find(|c| c.id == "doge"),
+        Some(&CurrencyResponse {
+            id: "doge".to_string(),
+            enabled: true,
+            feed_id: "dogecoin".to_string(),
+            volatility: None,
+        })
+    );
+
+    // Point `atom` to a different feed, prices are queried from oracles by feed id
+    let feed_msg = ExecuteMsg::SetCurrencyFeed {
+        id: "atom".to_string(),
+        feed_id: "cosmos".to_string(),
+    };
+    let res = router.execute_contract(creator.clone(), wager_contract.clone(), &feed_msg, &[]);
+    assert!(res.is_ok());
+
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "1.0");
+    set_oracle_price(router, &oracle, &creator, "cosmos".to_string(), "100.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "100.0");
+    setup_wager(router, &wager_contract, &sender, &peer);
+
+    let query_msg = QueryMsg::Wager {
+        token: token(TOKEN1_ID),
+    };
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract, &query_msg)
+        .unwrap();
+    let price = Decimal::from_str("100.0").unwrap();
+    assert_eq!(res.wager.wagers.0.opening_prices, vec![price]);
+    assert_eq!(res.wager.wagers.1.opening_prices, vec![price]);
+}
+
+#[test]
+fn try_denoms() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+    let oracle = setup_oracles(router, &creator, &wager_contract, 1, 1).remove(0);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "100.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "100.0");
+
+    let ibc_denom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+    for addr in [&sender, &peer] {
+        router
+            .sudo(CwSudoMsg::Bank(BankSudo::Mint {
+                to_address: addr.to_string(),
+                amount: coins(100_000_000, ibc_denom),
+            }))
+            .unwrap();
+    }
+
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN1_ID),
+        currency: "atom".into(),
+        against_currencies: vec!["stars".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+
+    // Attempt to wager with a denom that is not accepted
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &wager_msg,
+            &[coin(10_000_000, ibc_denom)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DenomNotAccepted {
+            denom: ibc_denom.to_string()
+        }
+    );
+
+    // Attempt to accept the denom from a non-admin address
+    // Expects: failure
+    let set_denom_msg = ExecuteMsg::SetDenom {
+        denom: Denom::Native(ibc_denom.to_string()),
+        amounts: vec![Uint128::from(10_000_000u128)],
+    };
+    let err = router
+        .execute_contract(sender.clone(), wager_contract.clone(), &set_denom_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Accept the denom from the admin
+    // Expects: success
+    let res = router.execute_contract(creator.clone(), wager_contract.clone(), &set_denom_msg, &[]);
+    assert!(res.is_ok());
+
+    let res: DenomsResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &QueryMsg::Denoms {})
+        .unwrap();
+    assert_eq!(res.denoms.len(), 2);
+    assert_eq!(
+        res.denoms[0],
+        DenomResponse {
+            denom: Denom::Native(ibc_denom.to_string()),
+            amounts: vec![Uint128::from(10_000_000u128)],
+        }
+    );
+
+    // Attempt to wager an amount that is not allowed for the denom
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &wager_msg,
+            &[coin(100_000_000, ibc_denom)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "amount".into()
+        }
+    );
+
+    // Submit a wager for matchmaking with the denom
+    // Expects: success
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(10_000_000, ibc_denom)],
+    );
+    assert!(res.is_ok());
+
+    // A wager in STARS is not matched with a wager in another denom
+    let peer_wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN2_ID),
+        currency: "stars".into(),
+        against_currencies: vec!["atom".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &peer_wager_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "matchmake")));
+
+    let cancel_msg = ExecuteMsg::Cancel {
+        token: token(TOKEN2_ID),
+    };
+    let res = router.execute_contract(peer.clone(), wager_contract.clone(), &cancel_msg, &[]);
+    assert!(res.is_ok());
+
+    // A wager in the same denom is matched
+    // Expects: success
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &peer_wager_msg,
+        &[coin(10_000_000, ibc_denom)],
+    );
+    assert!(res.is_ok());
+
+    let query_msg = QueryMsg::Wager {
+        token: token(TOKEN1_ID),
+    };
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.wager.denom, Denom::Native(ibc_denom.to_string()));
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "110.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "150.5");
+
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN2_ID), token(TOKEN1_ID)),
+        attestations: None,
+    };
+    let res = router.execute_contract(
+        Addr::unchecked(KEEPER),
+        wager_contract,
+        &set_winner_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // 20 ATOM pot minus 4% fee and 1% that is not fair burned
+    let balance = router.wrap().query_balance(&peer, ibc_denom).unwrap();
+    assert_eq!(balance.amount, Uint128::from(109_000_000u128));
+
+    // The fair burn share goes to the fee collector along with the fee minus the keeper reward
+    let balance = router.wrap().query_balance(&creator, ibc_denom).unwrap();
+    assert_eq!(balance.amount, Uint128::from(920_000u128));
+    let balance = router.wrap().query_balance(KEEPER, ibc_denom).unwrap();
+    assert_eq!(balance.amount, Uint128::from(80_000u128));
+
+    // The STARS stake was refunded in full
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_000_000_000u128));
+}
+
+// Instantiates a cw20 token with a balance for `sender` and `peer`
+fn setup_cw20(router: &mut StargazeApp, creator: &Addr, sender: &Addr, peer: &Addr) -> Addr {
+    let cw20_id = router.store_code(contract_cw20());
+    let msg = cw20_base::msg::InstantiateMsg {
+        name: "Wizard Token".to_string(),
+        symbol: "WIZ".to_string(),
+        decimals: 6,
+        initial_balances: vec![
+            Cw20Coin {
+                address: sender.to_string(),
+                amount: Uint128::from(100_000_000u128),
+            },
+            Cw20Coin {
+                address: peer.to_string(),
+                amount: Uint128::from(100_000_000u128),
+            },
+        ],
+        mint: None,
+        marketing: None,
+    };
+    router
+        .instantiate_contract(cw20_id, creator.clone(), &msg, &[], "cw20", None)
+        .unwrap()
+}
+
+fn cw20_balance(router: &StargazeApp, cw20: &Addr, address: &Addr) -> Uint128 {
+    let res: BalanceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            cw20,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+    res.balance
+}
+
+#[test]
+fn try_cw20() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+    let oracle = setup_oracles(router, &creator, &wager_contract, 1, 1).remove(0);
+    let cw20 = setup_cw20(router, &creator, &sender, &peer);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "100.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "100.0");
+
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: wager_contract.to_string(),
+        amount: Uint128::from(10_000_000u128),
+        msg: to_binary(&Cw20HookMsg::Wager {
+            token: token(TOKEN1_ID),
+            currency: "atom".into(),
+            against_currencies: vec!["stars".to_string()],
+            expiry: 60,
+            settlement_mode: None,
+            direction: None,
+            wager_type: None,
+            against_amount: None,
+            scoring_mode: None,
+        })
+        .unwrap(),
+    };
+
+    // Attempt to wager with a token that is not accepted
+    // Expects: failure
+    let err = router
+        .execute_contract(sender.clone(), cw20.clone(), &send_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DenomNotAccepted {
+            denom: cw20.to_string()
+        }
+    );
+
+    let set_denom_msg = ExecuteMsg::SetDenom {
+        denom: Denom::Cw20(cw20.clone()),
+        amounts: vec![Uint128::from(10_000_000u128)],
+    };
+    let res = router.execute_contract(creator.clone(), wager_contract.clone(), &set_denom_msg, &[]);
+    assert!(res.is_ok());
+
+    // Attempt to wager with the accepted token
+    // Expects: success
+    let res = router.execute_contract(sender.clone(), cw20.clone(), &send_msg, &[]);
+    assert!(res.is_ok());
+    assert_eq!(
+        cw20_balance(router, &cw20, &sender),
+        Uint128::from(90_000_000u128)
+    );
+
+    // Cancelling refunds the stake with a cw20 transfer
+    let cancel_msg = ExecuteMsg::Cancel {
+        token: token(TOKEN1_ID),
+    };
+    let res = router.execute_contract(sender.clone(), wager_contract.clone(), &cancel_msg, &[]);
+    assert!(res.is_ok());
+    assert_eq!(
+        cw20_balance(router, &cw20, &sender),
+        Uint128::from(100_000_000u128)
+    );
+
+    let res = router.execute_contract(sender.clone(), cw20.clone(), &send_msg, &[]);
+    assert!(res.is_ok());
+
+    // Attempt to match the wager from `peer` with the same token
+    // Expects: success
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: wager_contract.to_string(),
+        amount: Uint128::from(10_000_000u128),
+        msg: to_binary(&Cw20HookMsg::Wager {
+            token: token(TOKEN2_ID),
+            currency: "stars".into(),
+            against_currencies: vec!["atom".to_string()],
+            expiry: 60,
+            settlement_mode: None,
+            direction: None,
+            wager_type: None,
+            against_amount: None,
+            scoring_mode: None,
+        })
+        .unwrap(),
+    };
+    let res = router.execute_contract(peer.clone(), cw20.clone(), &send_msg, &[]);
+    assert!(res.is_ok());
+
+    let query_msg = QueryMsg::Wager {
+        token: token(TOKEN1_ID),
+    };
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.wager.denom, Denom::Cw20(cw20.clone()));
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+
+    set_oracle_price(router, &oracle, &creator, "atom".to_string(), "110.0");
+    set_oracle_price(router, &oracle, &creator, "stars".to_string(), "150.5");
+
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN2_ID), token(TOKEN1_ID)),
+        attestations: None,
+    };
+    let res = router.execute_contract(
+        Addr::unchecked(KEEPER),
+        wager_contract,
+        &set_winner_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // 20 WIZ pot minus 4% fee and 1% that is not fair burned
+    assert_eq!(
+        cw20_balance(router, &cw20, &peer),
+        Uint128::from(109_000_000u128)
+    );
+    assert_eq!(
+        cw20_balance(router, &cw20, &creator),
+        Uint128::from(920_000u128)
+    );
+    assert_eq!(
+        cw20_balance(router, &cw20, &Addr::unchecked(KEEPER)),
+        Uint128::from(80_000u128)
+    );
+}
+
+#[test]
+fn try_token_wager() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    // Deposit the flat fee and escrow the token as the stake
+    // Expects: success
+    for addr in [&sender, &peer] {
+        let res = router.execute_contract(
+            addr.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::Deposit {},
+            &[coin(5_000_000, NATIVE_DENOM)],
+        );
+        assert!(res.is_ok());
+    }
+
+    let send_nft_msg = Cw721ExecuteMsg::SendNft {
+        contract: wager_contract.to_string(),
+        token_id: TOKEN1_ID.to_string(),
+        msg: to_binary(&ReceiveMsg::WagerToken {
+            currency: "atom".into(),
+            against_currencies: vec!["stars".to_string()],
+            expiry: 60,
+            settlement_mode: None,
+            direction: None,
+            wager_type: None,
+            scoring_mode: None,
+        })
+        .unwrap(),
+    };
+    let res = router.execute_contract(sender.clone(), collection.clone(), &send_nft_msg, &[]);
+    assert!(res.is_ok());
+
+    // A wager with a fungible stake is not matched with a token wager
+    let wager_msg = ExecuteMsg::Wager {
+        token: token(TOKEN2_ID),
+        currency: "stars".into(),
+        against_currencies: vec!["atom".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &wager_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "matchmake")));
+
+    let cancel_msg = ExecuteMsg::Cancel {
+        token: token(TOKEN2_ID),
+    };
+    let res = router.execute_contract(peer.clone(), wager_contract.clone(), &cancel_msg, &[]);
+    assert!(res.is_ok());
+
+    // Raise the flat fee while the token of `sender` is matchmaking
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        params: ParamInfo {
+            token_wager_fee: Some(Uint128::from(8_000_000u128)),
+            ..Default::default()
+        },
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &update_config_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::Deposit {},
+        &[coin(3_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to match with another token wager that paid a different fee
+    // Expects: success, both fees are held until settlement
+    let fee_balance = router.wrap().query_balance(&creator, NATIVE_DENOM).unwrap();
+
+    let send_nft_msg = Cw721ExecuteMsg::SendNft {
+        contract: wager_contract.to_string(),
+        token_id: TOKEN2_ID.to_string(),
+        msg: to_binary(&ReceiveMsg::WagerToken {
+            currency: "stars".into(),
+            against_currencies: vec!["atom".to_string()],
+            expiry: 60,
+            settlement_mode: None,
+            direction: None,
+            wager_type: None,
+            scoring_mode: None,
+        })
+        .unwrap(),
+    };
+    let res = router.execute_contract(peer.clone(), collection.clone(), &send_nft_msg, &[]);
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&creator, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, fee_balance.amount);
+
+    let query_msg = QueryMsg::Wager {
+        token: token(TOKEN1_ID),
+    };
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &query_msg)
+        .unwrap();
+    assert_eq!(res.wager.stake_type, StakeType::Token);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "150.0"),
+        ],
+    );
+
+    // Attempt to set the winner
+    // Expects: success, `peer` takes the token of `sender` and gets its own back,
+    // the keeper is paid 10% of both fees
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN2_ID), token(TOKEN1_ID)),
+        attestations: None,
+    };
+    let res = router
+        .execute_contract(
+            creator.clone(),
+            wager_contract.clone(),
+            &set_winner_msg,
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "keeper_reward" && a.value == "1300000")));
+
+    let balance = router.wrap().query_balance(&creator, NATIVE_DENOM).unwrap();
+    assert_eq!(
+        balance.amount - fee_balance.amount,
+        Uint128::from(13_000_000u128)
+    );
+
+    for token_id in [TOKEN1_ID, TOKEN2_ID] {
+        let res: OwnerOfResponse = router
+            .wrap()
+            .query_wasm_smart(
+                collection.clone(),
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: token_id.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.owner, peer.to_string());
+    }
+
+    let res: TokenStatusResponse = router
+        .wrap()
+        .query_wasm_smart(
+            wager_contract,
+            &QueryMsg::TokenStatus {
+                token: token(TOKEN1_ID),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.token_status, TokenStatus::None);
+}
+
+// Instantiates a second collection and mints `token_id` to `owner`
+fn setup_collection(router: &mut StargazeApp, creator: &Addr, owner: &Addr, token_id: u32) -> Addr {
+    let collection_id = router.store_code(contract_mock_collection());
+    let collection = router
+        .instantiate_contract(
+            collection_id,
+            creator.clone(),
+            &cw721_base::InstantiateMsg {
+                name: "Collection".to_string(),
+                symbol: "COL".to_string(),
+                minter: creator.to_string(),
+            },
+            &[],
+            "collection",
+            None,
+        )
+        .unwrap();
+
+    let mint_msg = cw721_base::ExecuteMsg::<cw721_base::Extension>::Mint(cw721_base::MintMsg {
+        token_id: token_id.to_string(),
+        owner: owner.to_string(),
+        token_uri: None,
+        extension: None,
+    });
+    router
+        .execute_contract(creator.clone(), collection.clone(), &mint_msg, &[])
+        .unwrap();
+
+    collection
+}
+
+#[test]
+fn try_multi_collection() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    // The second collection has a token with the same id as the token of `sender`
+    let other_collection = setup_collection(router, &creator, &peer, TOKEN1_ID);
+    let other_token = (other_collection.clone(), TOKEN1_ID as u64);
+
+    let wager_msg = ExecuteMsg::Wager {
+        token: other_token.clone(),
+        currency: "stars".into(),
+        against_currencies: vec!["atom".to_string()],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+
+    // Attempt to wager a token of a collection that is not registered
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &wager_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnknownCollection {
+            collection: other_collection.to_string()
+        }
+    );
+
+    // Attempt to register the collection with the amounts of a denom listed twice
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            creator.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::SetCollection {
+                address: other_collection.to_string(),
+                amounts: Some(vec![
+                    (
+                        Denom::Native(NATIVE_DENOM.to_string()),
+                        vec![Uint128::from(100_000_000u128)],
+                    ),
+                    (
+                        Denom::Native(NATIVE_DENOM.to_string()),
+                        vec![Uint128::from(250_000_000u128)],
+                    ),
+                ]),
+                expiries: None,
+                fee_bps: None,
+                matchmaking_expiry: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "amounts".into()
+        }
+    );
+
+    // Register the collection with amounts for another denom only
+    // Expects: success, STARS stakes keep the amounts of the denom
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::SetCollection {
+            address: other_collection.to_string(),
+            amounts: Some(vec![(
+                Denom::Native("uatom".to_string()),
+                vec![Uint128::from(100_000_000u128)],
+            )]),
+            expiries: None,
+            fee_bps: None,
+            matchmaking_expiry: None,
+        },
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &wager_msg,
+        &[coin(250_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    let res = router.execute_contract(
+        peer.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::Cancel {
+            token: other_token.clone(),
+        },
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // Register the collection with its own amounts and a 10% fee
+    // Expects: success
+    let set_collection_msg = ExecuteMsg::SetCollection {
+        address: other_collection.to_string(),
+        amounts: Some(vec![(
+            Denom::Native(NATIVE_DENOM.to_string()),
+            vec![Uint128::from(100_000_000u128)],
+        )]),
+        expiries: None,
+        fee_bps: Some(1000),
+        matchmaking_expiry: None,
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &set_collection_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let res: CollectionsResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &QueryMsg::Collections {})
+        .unwrap();
+    assert_eq!(res.collections.len(), 2);
+
+    // Attempt to wager an amount that is only allowed by the config
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &wager_msg,
+            &[coin(250_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "amount".into()
+        }
+    );
+
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::Wager {
+            token: token(TOKEN1_ID),
+            currency: "atom".into(),
+            against_currencies: vec!["stars".to_string()],
+            expiry: 60,
+            settlement_mode: None,
+            direction: None,
+            wager_type: None,
+            against_amount: None,
+            scoring_mode: None,
+        },
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to match tokens of different collections while cross-collection matches are disabled
+    // Expects: success, the wager is left in matchmaking
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &wager_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "matchmake")));
+
+    let res: MatchmakingResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &QueryMsg::Matchmaking {})
+        .unwrap();
+    assert_eq!(res.matchmaking.len(), 2);
+
+    let cancel_msg = ExecuteMsg::Cancel {
+        token: other_token.clone(),
+    };
+    let res = router.execute_contract(peer.clone(), wager_contract.clone(), &cancel_msg, &[]);
+    assert!(res.is_ok());
+
+    // Allow cross-collection matches
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        params: ParamInfo {
+            cross_collection: Some(true),
+            ..Default::default()
+        },
+    };
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &update_config_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to match tokens of different collections
+    // Expects: success
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &wager_msg,
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "wager")));
+
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(
+            wager_contract.clone(),
+            &QueryMsg::Wager {
+                token: other_token.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.wager.wagers.0.token.collection, other_collection);
+    assert_eq!(res.wager.wagers.1.token.collection, collection);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "150.0"),
+        ],
+    );
+
+    // Attempt to set the winner
+    // Expects: success, the higher fee of the second collection is charged
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (other_token, token(TOKEN1_ID)),
+        attestations: None,
+    };
+    let res = router.execute_contract(creator, wager_contract, &set_winner_msg, &[]);
+    assert!(res.is_ok());
+
+    // 200 STARS minus the 10% fee and the 1% fair burn
+    let new_balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(
+        new_balance.amount - balance.amount,
+        Uint128::from(178_000_000u128)
+    );
+}
+
+#[test]
+fn try_short_wager() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    // `sender` bets that Atom falls more than Stars
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::Wager {
+            token: token(TOKEN1_ID),
+            currency: "atom".into(),
+            against_currencies: vec!["stars".to_string()],
+            expiry: 60,
+            settlement_mode: None,
+            direction: Some(Direction::Short),
+            wager_type: None,
+            against_amount: None,
+            scoring_mode: None,
+        },
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to match with a long wager
+    // Expects: success, the wager is left in matchmaking
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::Wager {
+                token: token(TOKEN2_ID),
+                currency: "stars".into(),
+                against_currencies: vec!["atom".to_string()],
+                expiry: 60,
+                settlement_mode: None,
+                direction: None,
+                wager_type: None,
+                against_amount: None,
+                scoring_mode: None,
+            },
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "matchmake")));
+
+    let cancel_msg = ExecuteMsg::Cancel {
+        token: token(TOKEN2_ID),
+    };
+    let res = router.execute_contract(peer.clone(), wager_contract.clone(), &cancel_msg, &[]);
+    assert!(res.is_ok());
+
+    // Attempt to match with a short wager
+    // Expects: success
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::Wager {
+                token: token(TOKEN2_ID),
+                currency: "stars".into(),
+                against_currencies: vec!["atom".to_string()],
+                expiry: 60,
+                settlement_mode: None,
+                direction: Some(Direction::Short),
+                wager_type: None,
+                against_amount: None,
+                scoring_mode: None,
+            },
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "wager")));
+
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(
+            wager_contract.clone(),
+            &QueryMsg::Wager {
+                token: token(TOKEN1_ID),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.wager.wagers.0.direction, Direction::Short);
+    assert_eq!(res.wager.wagers.1.direction, Direction::Short);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+
+    // Both rise, Atom the least
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "110.0"),
+            ("stars".to_string(), "150.0"),
+        ],
+    );
+
+    // Attempt to set the winner
+    // Expects: success, `sender` wins
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN2_ID), token(TOKEN1_ID)),
+        attestations: None,
+    };
+    let res = router
+        .execute_contract(creator, wager_contract, &set_winner_msg, &[])
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "winner" && a.value == sender.as_str())));
+}
+
+#[test]
+fn try_over_under() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[("atom".to_string(), "100.0")],
+    );
+
+    let over_under = |token_id: u32, direction: Direction, strike: &str| ExecuteMsg::Wager {
+        token: token(token_id),
+        currency: "atom".into(),
+        against_currencies: vec![],
+        expiry: 60,
+        settlement_mode: None,
+        direction: Some(direction),
+        wager_type: Some(WagerType::OverUnder {
+            strike: Decimal::from_str(strike).unwrap(),
+        }),
+        against_amount: None,
+        scoring_mode: None,
+    };
+
+    // Attempt to make an over/under wager against other currencies
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::Wager {
+                token: token(TOKEN1_ID),
+                currency: "atom".into(),
+                against_currencies: vec!["stars".to_string()],
+                expiry: 60,
+                settlement_mode: None,
+                direction: Some(Direction::Long),
+                wager_type: Some(WagerType::OverUnder {
+                    strike: Decimal::from_str("105.0").unwrap(),
+                }),
+                against_amount: None,
+                scoring_mode: None,
+            },
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "against_currencies".into()
+        }
+    );
+
+    // `sender` bets that Atom closes over 105
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &over_under(TOKEN1_ID, Direction::Long, "105.0"),
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to match with the same side, or with another strike
+    // Expects: success, the wagers are left in matchmaking
+    for (direction, strike) in [(Direction::Long, "105.0"), (Direction::Short, "110.0")] {
+        let res = router
+            .execute_contract(
+                peer.clone(),
+                wager_contract.clone(),
+                &over_under(TOKEN2_ID, direction, strike),
+                &[coin(100_000_000, NATIVE_DENOM)],
+            )
+            .unwrap();
+        assert!(res.events.iter().any(|e| e
+            .attributes
+            .iter()
+            .any(|a| a.key == "action" && a.value == "matchmake")));
+
+        let cancel_msg = ExecuteMsg::Cancel {
+            token: token(TOKEN2_ID),
+        };
+        let res = router.execute_contract(peer.clone(), wager_contract.clone(), &cancel_msg, &[]);
+        assert!(res.is_ok());
+    }
+
+    // Attempt to take the under side
+    // Expects: success
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &over_under(TOKEN2_ID, Direction::Short, "105.0"),
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "wager")));
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[("atom".to_string(), "110.0")],
+    );
+
+    // Attempt to push a zero price
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            creator.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::UpdatePrices {
+                prices: vec![("atom".to_string(), Decimal::zero())],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "prices".into()
+        }
+    );
+
+    // Atom falls under the strike after the wager expired
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 1000,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[("atom".to_string(), "90.0")],
+    );
+
+    // Attempt to set the winner
+    // Expects: success, Atom closed over the strike so `sender` wins, the later price is ignored
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN2_ID), token(TOKEN1_ID)),
+        attestations: None,
+    };
+    let res = router
+        .execute_contract(creator, wager_contract, &set_winner_msg, &[])
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "winner" && a.value == sender.as_str())));
+}
+
+#[test]
+fn try_odds() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    // Attempt to ask for an opponent stake that is not allowed
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::Wager {
+                token: token(TOKEN1_ID),
+                currency: "atom".into(),
+                against_currencies: vec!["stars".to_string()],
+                expiry: 60,
+                settlement_mode: None,
+                direction: None,
+                wager_type: None,
+                against_amount: Some(Uint128::from(300_000_000u128)),
+                scoring_mode: None,
+            },
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "amount".into()
+        }
+    );
+
+    // `sender` stakes 100 against 250 from the opponent
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &ExecuteMsg::Wager {
+            token: token(TOKEN1_ID),
+            currency: "atom".into(),
+            against_currencies: vec!["stars".to_string()],
+            expiry: 60,
+            settlement_mode: None,
+            direction: None,
+            wager_type: None,
+            against_amount: Some(Uint128::from(250_000_000u128)),
+            scoring_mode: None,
+        },
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to match at even odds
+    // Expects: success, the wager is left in matchmaking
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::Wager {
+                token: token(TOKEN2_ID),
+                currency: "stars".into(),
+                against_currencies: vec!["atom".to_string()],
+                expiry: 60,
+                settlement_mode: None,
+                direction: None,
+                wager_type: None,
+                against_amount: None,
+                scoring_mode: None,
+            },
+            &[coin(250_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "matchmake")));
+
+    let cancel_msg = ExecuteMsg::Cancel {
+        token: token(TOKEN2_ID),
+    };
+    let res = router.execute_contract(peer.clone(), wager_contract.clone(), &cancel_msg, &[]);
+    assert!(res.is_ok());
+
+    // Attempt to match at the offered odds
+    // Expects: success
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::Wager {
+                token: token(TOKEN2_ID),
+                currency: "stars".into(),
+                against_currencies: vec!["atom".to_string()],
+                expiry: 60,
+                settlement_mode: None,
+                direction: None,
+                wager_type: None,
+                against_amount: Some(Uint128::from(100_000_000u128)),
+                scoring_mode: None,
+            },
+            &[coin(250_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "wager")));
+
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(
+            wager_contract.clone(),
+            &QueryMsg::Wager {
+                token: token(TOKEN1_ID),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.wager.wagers.0.stake, Uint128::from(250_000_000u128));
+    assert_eq!(res.wager.wagers.1.stake, Uint128::from(100_000_000u128));
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "150.0"),
+            ("stars".to_string(), "110.0"),
+        ],
+    );
+
+    // Attempt to set the winner
+    // Expects: success, `sender` wins the whole pot of 350 minus 5% in fees
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN2_ID), token(TOKEN1_ID)),
+        attestations: None,
+    };
+    let res = router
+        .execute_contract(creator, wager_contract, &set_winner_msg, &[])
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "winner" && a.value == sender.as_str())));
+
+    let balance = router.wrap().query_balance(&sender, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_232_500_000u128));
+
+    let balance = router.wrap().query_balance(&peer, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(1_999_999_750_000_000u128));
+}
+
+#[test]
+fn try_basket() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("osmo".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    let basket = |weights: &[(&str, &str)]| {
+        WagerCurrency::Basket(
+            weights
+                .iter()
+                .map(|(c, w)| (c.to_string(), Decimal::from_str(w).unwrap()))
+                .collect(),
+        )
+    };
+    let basket_wager = |token_id: u32, currency: WagerCurrency, against_currencies: Vec<String>| {
+        ExecuteMsg::Wager {
+            token: token(token_id),
+            currency,
+            against_currencies,
+            expiry: 60,
+            settlement_mode: None,
+            direction: None,
+            wager_type: None,
+            against_amount: None,
+            scoring_mode: None,
+        }
+    };
+
+    // Attempt to wager on invalid baskets
+    // Expects: failure
+    for (currency, against_currencies, param) in [
+        // Weights that do not add up to one
+        (
+            basket(&[("atom", "0.5"), ("osmo", "0.3")]),
+            vec![],
+            "currency",
+        ),
+        // More currencies than `max_currencies`
+        (
+            basket(&[
+                ("atom", "0.25"),
+                ("osmo", "0.25"),
+                ("stars", "0.25"),
+                ("btc", "0.25"),
+            ]),
+            vec![],
+            "currency",
+        ),
+        // The same currency twice
+        (
+            basket(&[("atom", "0.5"), ("atom", "0.5")]),
+            vec![],
+            "currency",
+        ),
+        // Currencies to wager against
+        (
+            basket(&[("atom", "0.5"), ("osmo", "0.5")]),
+            vec!["stars".to_string()],
+            "against_currencies",
+        ),
+    ] {
+        let err = router
+            .execute_contract(
+                sender.clone(),
+                wager_contract.clone(),
+                &basket_wager(TOKEN1_ID, currency, against_currencies),
+                &[coin(100_000_000, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidParameter {
+                param: param.into()
+            }
+        );
+    }
+
+    // `sender` backs 50% Atom, 30% Osmo and 20% Stars
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &basket_wager(
+            TOKEN1_ID,
+            basket(&[("atom", "0.5"), ("osmo", "0.3"), ("stars", "0.2")]),
+            vec![],
+        ),
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to match with a single currency
+    // Expects: success, the wager is left in matchmaking
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &basket_wager(TOKEN2_ID, "stars".into(), vec!["atom".to_string()]),
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "matchmake")));
+
+    let cancel_msg = ExecuteMsg::Cancel {
+        token: token(TOKEN2_ID),
+    };
+    let res = router.execute_contract(peer.clone(), wager_contract.clone(), &cancel_msg, &[]);
+    assert!(res.is_ok());
+
+    // Attempt to match with another basket
+    // Expects: success
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &basket_wager(
+                TOKEN2_ID,
+                basket(&[("atom", "0.5"), ("stars", "0.5")]),
+                vec![],
+            ),
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "wager")));
+
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(
+            wager_contract.clone(),
+            &QueryMsg::Wager {
+                token: token(TOKEN1_ID),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.wager.wagers.1.basket,
+        vec![
+            ("atom".to_string(), Decimal::percent(50)),
+            ("osmo".to_string(), Decimal::percent(30)),
+            ("stars".to_string(), Decimal::percent(20)),
+        ]
+    );
+    assert_eq!(res.wager.wagers.1.opening_prices.len(), 3);
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "120.0"),
+            ("osmo".to_string(), "90.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    // Attempt to set the winner
+    // Expects: success, the basket of `peer` gains 10% against 7% for `sender`
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN2_ID), token(TOKEN1_ID)),
+        attestations: None,
+    };
+    let res = router
+        .execute_contract(creator, wager_contract, &set_winner_msg, &[])
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "winner" && a.value == peer.as_str())));
+}
+
+#[test]
+fn try_disabled_basket() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("osmo".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    let basket_wager = |token_id: u32, weights: &[(&str, &str)]| ExecuteMsg::Wager {
+        token: token(token_id),
+        currency: WagerCurrency::Basket(
+            weights
+                .iter()
+                .map(|(c, w)| (c.to_string(), Decimal::from_str(w).unwrap()))
+                .collect(),
+        ),
+        against_currencies: vec![],
+        expiry: 60,
+        settlement_mode: None,
+        direction: None,
+        wager_type: None,
+        against_amount: None,
+        scoring_mode: None,
+    };
+
+    // `sender` backs 50% Atom and 50% Osmo
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &basket_wager(TOKEN1_ID, &[("atom", "0.5"), ("osmo", "0.5")]),
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    let disable_msg = ExecuteMsg::DisableCurrency {
+        id: "osmo".to_string(),
+    };
+    let res = router.execute_contract(creator.clone(), wager_contract.clone(), &disable_msg, &[]);
+    assert!(res.is_ok());
+
+    // Attempt to match with the basket holding a disabled currency
+    // Expects: success, the wager is left in matchmaking
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &basket_wager(TOKEN2_ID, &[("atom", "0.5"), ("stars", "0.5")]),
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "matchmake")));
+
+    // The resting item can still be cancelled
+    let cancel_msg = ExecuteMsg::Cancel {
+        token: token(TOKEN1_ID),
+    };
+    let res = router.execute_contract(sender.clone(), wager_contract.clone(), &cancel_msg, &[]);
+    assert!(res.is_ok());
+
+    let balance = router.wrap().query_balance(&sender, NATIVE_DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::from(2_000_000_000_000_000u128));
+}
+
+#[test]
+fn try_volatility_normalized() {
+    let router = &mut custom_mock_app();
+
+    let (sender, peer, creator) = setup_accounts(router).unwrap();
+    let (wager_contract, collection) = setup_contracts(router, &creator).unwrap();
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 100,
+    );
+
+    setup_tokens(router, &creator, &sender, &peer, &collection);
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "100.0"),
+            ("stars".to_string(), "100.0"),
+        ],
+    );
+
+    let normalized_wager =
+        |token_id: u32, currency: &str, against_currency: &str| ExecuteMsg::Wager {
+            token: token(token_id),
+            currency: currency.into(),
+            against_currencies: vec![against_currency.to_string()],
+            expiry: 60,
+            settlement_mode: None,
+            direction: None,
+            wager_type: None,
+            against_amount: None,
+            scoring_mode: Some(ScoringMode::VolatilityNormalized),
+        };
+
+    // Attempt to make a volatility-normalized wager before the volatility is set
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &normalized_wager(TOKEN1_ID, "atom", "stars"),
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::VolatilityNotSet {
+            currency: "atom".into()
+        }
+    );
+
+    // Attempt to set a volatility factor from a user
+    // Expects: failure
+    let volatility_msg = |id: &str, volatility: &str| ExecuteMsg::SetCurrencyVolatility {
+        id: id.to_string(),
+        volatility: Decimal::from_str(volatility).unwrap(),
+    };
+    let err = router
+        .execute_contract(
+            sender.clone(),
+            wager_contract.clone(),
+            &volatility_msg("atom", "2.0"),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Attempt to set a zero volatility factor
+    // Expects: failure
+    let err = router
+        .execute_contract(
+            creator.clone(),
+            wager_contract.clone(),
+            &volatility_msg("atom", "0"),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidParameter {
+            param: "volatility".into()
+        }
+    );
+
+    // Atom is four times as volatile as Stars
+    for (id, volatility) in [("atom", "2.0"), ("stars", "0.5")] {
+        let res = router.execute_contract(
+            creator.clone(),
+            wager_contract.clone(),
+            &volatility_msg(id, volatility),
+            &[],
+        );
+        assert!(res.is_ok());
+    }
+
+    let res: CurrenciesResponse = router
+        .wrap()
+        .query_wasm_smart(wager_contract.clone(), &QueryMsg::Currencies {})
+        .unwrap();
+    assert_eq!(
+        res.currencies
+            .iter()
+            .find(|c| c.id == "atom")
+            .unwrap()
+            .volatility,
+        Some(Decimal::from_str("2.0").unwrap())
+    );
+
+    let res = router.execute_contract(
+        sender.clone(),
+        wager_contract.clone(),
+        &normalized_wager(TOKEN1_ID, "atom", "stars"),
+        &[coin(100_000_000, NATIVE_DENOM)],
+    );
+    assert!(res.is_ok());
+
+    // Attempt to match with a raw wager
+    // Expects: success, the wager is left in matchmaking
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &ExecuteMsg::Wager {
+                token: token(TOKEN2_ID),
+                currency: "stars".into(),
+                against_currencies: vec!["atom".to_string()],
+                expiry: 60,
+                settlement_mode: None,
+                direction: None,
+                wager_type: None,
+                against_amount: None,
+                scoring_mode: None,
+            },
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "matchmake")));
+
+    let cancel_msg = ExecuteMsg::Cancel {
+        token: token(TOKEN2_ID),
+    };
+    let res = router.execute_contract(peer.clone(), wager_contract.clone(), &cancel_msg, &[]);
+    assert!(res.is_ok());
+
+    // Attempt to match with a volatility-normalized wager
+    // Expects: success
+    let res = router
+        .execute_contract(
+            peer.clone(),
+            wager_contract.clone(),
+            &normalized_wager(TOKEN2_ID, "stars", "atom"),
+            &[coin(100_000_000, NATIVE_DENOM)],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "wager")));
+
+    let res: WagerResponse = router
+        .wrap()
+        .query_wasm_smart(
+            wager_contract.clone(),
+            &QueryMsg::Wager {
+                token: token(TOKEN1_ID),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.wager.scoring_mode, ScoringMode::VolatilityNormalized);
+
+    // The factors are snapshotted when the wager is matched, later updates do not apply to it
+    let res = router.execute_contract(
+        creator.clone(),
+        wager_contract.clone(),
+        &volatility_msg("atom", "0.1"),
+        &[],
+    );
+    assert!(res.is_ok());
+
+    setup_block_time(
+        router,
+        Timestamp::from_nanos(GENESIS_MINT_START_TIME).seconds() + 160,
+    );
+
+    // Atom rises more, but less than Stars once divided by their volatility
+    update_prices(
+        router,
+        &wager_contract,
+        &creator,
+        &[
+            ("atom".to_string(), "120.0"),
+            ("stars".to_string(), "108.0"),
+        ],
+    );
+
+    // Attempt to set the winner
+    // Expects: success, `peer` wins with 16% against 10% for `sender`
+    let set_winner_msg = ExecuteMsg::SetWinner {
+        wager_key: (token(TOKEN2_ID), token(TOKEN1_ID)),
+        attestations: None,
+    };
+    let res = router
+        .execute_contract(creator, wager_contract, &set_winner_msg, &[])
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "winner" && a.value == peer.as_str())));
+}
@@ -0,0 +1,309 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Api, Decimal, Deps, Order, QuerierWrapper, StdResult, Storage, Timestamp, Uint128,
+};
+use cw_storage_plus::Bound;
+use sha2::{Digest, Sha256};
+
+use crate::currency::load_currency;
+use crate::error::ContractError;
+use crate::msg::{OracleInfo, PriceAttestation};
+use crate::state::{
+    Config, CurrencyId, Oracle, OracleQueryFormat, Price, SignerKeyType, ORACLE_OBSERVATIONS,
+    PRICES, PRICE_OBSERVATIONS,
+};
+
+/// Query sent to an oracle using `OracleQueryFormat::Currency`
+#[cw_serde]
+pub enum OracleQueryMsg {
+    Price { currency: CurrencyId },
+}
+
+/// Query sent to an oracle using `OracleQueryFormat::Symbol`
+#[cw_serde]
+pub enum SymbolQueryMsg {
+    Price { symbol: String },
+}
+
+pub fn validate_oracles(api: &dyn Api, oracles: Vec<OracleInfo>) -> StdResult<Vec<Oracle>> {
+    oracles
+        .into_iter()
+        .map(|oracle| {
+            Ok(Oracle {
+                address: api.addr_validate(&oracle.address)?,
+                query_format: oracle.query_format,
+            })
+        })
+        .collect()
+}
+
+// Query the latest price of a currency from an oracle contract by its feed id
+pub fn query_price(querier: &QuerierWrapper, oracle: &Oracle, feed_id: &str) -> StdResult<Price> {
+    match oracle.query_format {
+        OracleQueryFormat::Currency => querier.query_wasm_smart(
+            oracle.address.clone(),
+            &OracleQueryMsg::Price {
+                currency: feed_id.to_string(),
+            },
+        ),
+        OracleQueryFormat::Symbol => querier.query_wasm_smart(
+            oracle.address.clone(),
+            &SymbolQueryMsg::Price {
+                symbol: feed_id.to_uppercase(),
+            },
+        ),
+    }
+}
+
+// Load the price of the feed in effect at `time`
+fn load_feed_price(
+    storage: &dyn Storage,
+    currency: &CurrencyId,
+    time: Timestamp,
+) -> StdResult<Option<Price>> {
+    let price = PRICES.may_load(storage, currency.to_string())?;
+    if price.iter().all(|p| p.updated_at <= time) {
+        return Ok(price);
+    }
+
+    // The feed was updated since `time`, use the observation in effect then
+    PRICE_OBSERVATIONS
+        .prefix(currency.to_string())
+        .range(
+            storage,
+            None,
+            Some(Bound::inclusive(time.seconds())),
+            Order::Descending,
+        )
+        .next()
+        .transpose()
+        .map(|observation| {
+            observation.map(|(updated_at, price)| Price {
+                price,
+                updated_at: Timestamp::from_seconds(updated_at),
+            })
+        })
+}
+
+// Load the price of an oracle in effect at `time`, its latest price unless it was
+// updated since, in which case the latest price recorded with `RecordPrices`
+fn load_oracle_price(
+    deps: Deps,
+    oracle: &Oracle,
+    feed_id: &str,
+    time: Timestamp,
+) -> StdResult<Option<Price>> {
+    if let Ok(price) = query_price(&deps.querier, oracle, feed_id) {
+        if price.updated_at <= time {
+            return Ok(Some(price));
+        }
+    }
+
+    ORACLE_OBSERVATIONS
+        .prefix((feed_id.to_string(), &oracle.address))
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::inclusive(time.nanos())),
+            Order::Descending,
+        )
+        .next()
+        .transpose()
+        .map(|observation| {
+            observation.map(|(updated_at, price)| Price {
+                price,
+                updated_at: Timestamp::from_nanos(updated_at),
+            })
+        })
+}
+
+// Load the median price of a currency at `time` from the configured oracles,
+// otherwise from the price feed. Prices older than `max_price_age` or updated
+// after `time`, zero prices and oracles that fail to respond are ignored.
+pub fn load_price(
+    deps: Deps,
+    config: &Config,
+    currency: &CurrencyId,
+    time: Timestamp,
+) -> Result<Decimal, ContractError> {
+    let (prices, min_price_sources) = if config.oracles.is_empty() {
+        let price = load_feed_price(deps.storage, currency, time)?;
+        (price.into_iter().collect::<Vec<_>>(), 1)
+    } else {
+        let feed_id = load_currency(deps.storage, currency)?.feed_id;
+        let prices = config
+            .oracles
+            .iter()
+            .map(|oracle| load_oracle_price(deps, oracle, &feed_id, time))
+            .collect::<StdResult<Vec<_>>>()?;
+        (
+            prices.into_iter().flatten().collect(),
+            config.min_price_sources as usize,
+        )
+    };
+
+    let mut prices = prices
+        .into_iter()
+        .filter(|p| {
+            p.updated_at <= time
+                && p.updated_at.plus_seconds(config.max_price_age) >= time
+                && !p.price.is_zero()
+        })
+        .map(|p| p.price)
+        .collect::<Vec<_>>();
+
+    if prices.is_empty() || prices.len() < min_price_sources {
+        return Err(ContractError::InsufficientPriceSources {
+            currency: currency.to_string(),
+        });
+    }
+
+    prices.sort();
+
+    let mid = prices.len() / 2;
+    if prices.len() % 2 == 0 {
+        Ok((prices[mid - 1] + prices[mid]) * Decimal::percent(50))
+    } else {
+        Ok(prices[mid])
+    }
+}
+
+// Verify that a price report is signed by at least `signature_threshold` of
+// the configured signers
+pub fn verify_attestation(
+    deps: Deps,
+    config: &Config,
+    attestation: &PriceAttestation,
+) -> Result<(), ContractError> {
+    let hash = Sha256::digest(to_binary(&attestation.report)?.as_slice());
+
+    let signed = config
+        .signers
+        .iter()
+        .filter(|signer| {
+            attestation.signatures.iter().any(|s| {
+                s.pubkey == signer.pubkey
+                    && match signer.key_type {
+                        SignerKeyType::Secp256k1 => {
+                            deps.api
+                                .secp256k1_verify(&hash, &s.signature, &signer.pubkey)
+                        }
+                        SignerKeyType::Ed25519 => {
+                            deps.api.ed25519_verify(&hash, &s.signature, &signer.pubkey)
+                        }
+                    }
+                    .unwrap_or(false)
+            })
+        })
+        .count();
+
+    if config.signers.is_empty() || signed < config.signature_threshold as usize {
+        return Err(ContractError::InvalidAttestation {
+            currency: attestation.report.currency.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+// Load the price of a currency at `time` from a signed price report if attestations
+// are given, otherwise from the oracles or the price feed. A set of attestations must
+// cover every currency, it never falls back to the price feed.
+pub fn load_attested_price(
+    deps: Deps,
+    config: &Config,
+    attestations: Option<&[PriceAttestation]>,
+    currency: &CurrencyId,
+    time: Timestamp,
+) -> Result<Decimal, ContractError> {
+    let attestations = match attestations {
+        Some(attestations) => attestations,
+        None => return load_price(deps, config, currency, time),
+    };
+
+    let attestation = attestations
+        .iter()
+        .find(|a| &a.report.currency == currency)
+        .ok_or_else(|| ContractError::MissingAttestation {
+            currency: currency.to_string(),
+        })?;
+
+    verify_attestation(deps, config, attestation)?;
+
+    if attestation
+        .report
+        .timestamp
+        .plus_seconds(config.attestation_window)
+        < time
+    {
+        return Err(ContractError::InsufficientPriceSources {
+            currency: currency.to_string(),
+        });
+    }
+
+    // Reports taken after `time` would let the relayer pick the closing price
+    if attestation.report.timestamp > time || attestation.report.price.is_zero() {
+        return Err(ContractError::InvalidAttestation {
+            currency: currency.to_string(),
+        });
+    }
+
+    Ok(attestation.report.price)
+}
+
+// Load the time-weighted average price of a currency between `start` and `end`
+// from the price observations. The prices in effect at `start` and at `end`
+// must not be older than `max_price_age`.
+pub fn load_twap(
+    deps: Deps,
+    config: &Config,
+    currency: &CurrencyId,
+    start: Timestamp,
+    end: Timestamp,
+) -> Result<Decimal, ContractError> {
+    let insufficient = || ContractError::InsufficientPriceSources {
+        currency: currency.to_string(),
+    };
+    let (start, end) = (start.seconds(), end.seconds());
+    let observations = PRICE_OBSERVATIONS.prefix(currency.to_string());
+
+    // Price in effect at the start of the window
+    let (updated_at, mut price) = observations
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::inclusive(start)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?
+        .ok_or_else(insufficient)?;
+    if updated_at + config.max_price_age < start {
+        return Err(insufficient());
+    }
+
+    // Sum of each price weighted by the seconds it was in effect
+    let mut time = start;
+    let mut total = Uint128::zero();
+    for observation in observations.range(
+        deps.storage,
+        Some(Bound::exclusive(start)),
+        Some(Bound::inclusive(end)),
+        Order::Ascending,
+    ) {
+        let (updated_at, next_price) = observation?;
+        total += price.atomics() * Uint128::from(updated_at - time);
+        time = updated_at;
+        price = next_price;
+    }
+    if time + config.max_price_age < end {
+        return Err(insufficient());
+    }
+    total += price.atomics() * Uint128::from(end - time);
+
+    if start == end {
+        return Ok(price);
+    }
+
+    Ok(Decimal::new(total / Uint128::from(end - start)))
+}
@@ -1,42 +1,145 @@
 use cosmwasm_schema::cw_serde;
 
-use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, UniqueIndex};
-
-#[cw_serde]
-pub enum Currency {
-    Dot,
-    Avax,
-    Uni,
-    Atom,
-    Link,
-    Near,
-    Icp,
-    Sand,
-    Btc,
-    Eth,
-    Bnb,
-    Xrp,
-    Ada,
-    Doge,
-    Sol,
-    Mana,
-    Cake,
-    Ar,
-    Osmo,
-    Rune,
-    Luna,
-    Ustc,
-    Stars,
-    Mir,
+use cosmwasm_std::{Addr, Binary, Decimal, Timestamp, Uint128};
+use cw20::Denom;
+use cw_storage_plus::{
+    Index, IndexList, IndexedMap, Item, KeyDeserialize, Map, MultiIndex, UniqueIndex,
+};
+
+// Id of a currency in the registry (ex: "atom")
+pub type CurrencyId = String;
+
+#[cw_serde]
+pub struct CurrencyInfo {
+    // New wagers can only be made on enabled currencies
+    pub enabled: bool,
+    // Id used to query the price of the currency from oracles
+    pub feed_id: String,
+    // Factor the changes of the currency are divided by in volatility-normalized wagers
+    pub volatility: Option<Decimal>,
+}
+
+pub const CURRENCIES: Map<&str, CurrencyInfo> = Map::new("currencies");
+
+// Currencies backed by one side of a wager with their weights, which add up to one
+pub type Basket = Vec<(CurrencyId, Decimal)>;
+
+// Matchmaking bucket of the baskets of several currencies, never a valid currency id
+pub const BASKET_BUCKET: &str = "*";
+
+// Currency of a basket that only holds one
+pub fn single_currency(basket: &Basket) -> Option<&CurrencyId> {
+    match basket.as_slice() {
+        [(currency, _)] => Some(currency),
+        _ => None,
+    }
+}
+
+// Matchmaking bucket of a basket, baskets of several currencies are only matched with each other
+pub fn basket_bucket(basket: &Basket) -> String {
+    single_currency(basket)
+        .cloned()
+        .unwrap_or_else(|| BASKET_BUCKET.to_string())
+}
+
+// Denoms accepted as stakes by `denom_key`, with the list of wager amount options of each (ex: 50,100,250 STARS)
+pub const DENOMS: Map<&str, Vec<Uint128>> = Map::new("denoms");
+
+// Storage key of a denom, native denoms and cw20 tokens can never share a key
+pub fn denom_key(denom: &Denom) -> String {
+    match denom {
+        Denom::Native(denom) => format!("native:{}", denom),
+        Denom::Cw20(address) => format!("cw20:{}", address),
+    }
+}
+
+// Wager options of a collection, unset options fall back to the config
+#[cw_serde]
+#[derive(Default)]
+pub struct CollectionConfig {
+    // Lists of wager amount options by denom, replacing those of the listed denoms
+    pub amounts: Option<Vec<(Denom, Vec<Uint128>)>>,
+    pub expiries: Option<Vec<u64>>,
+    pub fee_percent: Option<Decimal>,
+    pub matchmaking_expiry: Option<u64>,
+}
+
+impl CollectionConfig {
+    // Amount options of the collection for a denom, if it overrides those of the denom
+    pub fn denom_amounts(&self, denom: &Denom) -> Option<&[Uint128]> {
+        self.amounts
+            .as_ref()?
+            .iter()
+            .find(|(d, _)| d == denom)
+            .map(|(_, amounts)| amounts.as_slice())
+    }
+}
+
+// NFT collections whose tokens can be wagered
+pub const COLLECTIONS: Map<&Addr, CollectionConfig> = Map::new("collections");
+
+#[cw_serde]
+pub enum SettlementMode {
+    // Compare the prices at expiry
+    Spot,
+    // Compare the time-weighted average prices over the last `window` seconds before expiry
+    Twap { window: u64 },
+}
+
+#[cw_serde]
+pub enum Direction {
+    // Wins if the currency performs better than the other side, or closes over the strike
+    Long,
+    // Wins if the currency performs worse than the other side, or closes under the strike
+    Short,
+}
+
+#[cw_serde]
+pub enum WagerType {
+    // Both sides race the change of their currency against each other
+    Race,
+    // Both sides wager on the same currency closing over or under `strike` at expiry
+    OverUnder { strike: Decimal },
+}
+
+#[cw_serde]
+pub enum ScoringMode {
+    // Rank both sides by the change of their prices
+    Raw,
+    // Divide the change of each currency by its volatility factor before ranking both sides
+    VolatilityNormalized,
+}
+
+#[cw_serde]
+pub enum StakeType {
+    // Both sides stake an amount of `denom`
+    Funds,
+    // Both sides stake their escrowed token, the winner takes the token of the loser
+    Token,
 }
 
 #[cw_serde]
 pub struct Wager {
     pub id: WagerKey,
-    pub currencies: (Currency, Currency),
-    pub amount: Uint128,
+    pub baskets: (Basket, Basket),
+    pub denom: Denom,
+    // Stakes of both sides, which differ when the wager was made at odds
+    pub stakes: (Uint128, Uint128),
+    // Flat fee paid by both sides of a token wager, held until the wager is settled
+    pub fee: Uint128,
     pub expires_at: Timestamp,
+    // Prices of the currencies of both baskets when the wager was matched
+    pub opening_prices: (Vec<Decimal>, Vec<Decimal>),
+    // Volatility factors of the currencies of both baskets when the wager was matched,
+    // ones for raw scoring
+    pub volatilities: (Vec<Decimal>, Vec<Decimal>),
+    pub settlement_mode: SettlementMode,
+    // Accounts that placed both sides of the wager
+    pub participants: (Addr, Addr),
+    pub directions: (Direction, Direction),
+    pub stake_type: StakeType,
+    pub wager_type: WagerType,
+    pub scoring_mode: ScoringMode,
 }
 
 #[cw_serde]
@@ -48,83 +151,317 @@ pub struct NFT {
 #[cw_serde]
 pub struct WagerInfo {
     pub token: NFT,
-    pub currency: Currency,
+    pub basket: Basket,
+    pub opening_prices: Vec<Decimal>,
+    pub participant: Addr,
+    pub direction: Direction,
+    pub stake: Uint128,
 }
 
 #[cw_serde]
 pub struct WagerExport {
-    pub amount: Uint128,
+    pub denom: Denom,
     pub expires_at: Timestamp,
+    pub settlement_mode: SettlementMode,
+    pub stake_type: StakeType,
+    pub wager_type: WagerType,
+    pub scoring_mode: ScoringMode,
     pub wagers: (WagerInfo, WagerInfo),
 }
 
 #[cw_serde]
 pub struct MatchmakingItem {
-    pub currency: Currency,
-    pub against_currencies: Vec<Currency>,
+    pub basket: Basket,
+    pub against_currencies: Vec<CurrencyId>,
     pub expires_at: Timestamp, // when this expires, remove it
     pub expiry: u64,           // expiry of the wager in seconds
+    pub denom: Denom,
     pub amount: Uint128,
+    // Stake required from the opponent, equal to `amount` at even odds
+    pub against_amount: Uint128,
+    pub settlement_mode: SettlementMode,
+    // Race items are only matched in the same direction, over/under items in the opposite one
+    pub direction: Direction,
+    pub wager_type: WagerType,
+    pub scoring_mode: ScoringMode,
+    // Address that paid the stake and is refunded when the item is removed
+    pub depositor: Addr,
+    // For token stakes, `amount` is the fee paid when the wager is matched
+    pub stake_type: StakeType,
+}
+
+impl MatchmakingItem {
+    // Amounts an item is bucketed and matched on, token stakes are matched whatever fee they paid
+    pub fn match_amounts(&self) -> (u128, u128) {
+        match self.stake_type {
+            StakeType::Funds => (self.amount.u128(), self.against_amount.u128()),
+            StakeType::Token => (0, 0),
+        }
+    }
 }
 
 #[cw_serde]
 pub struct MatchmakingItemExport {
     pub token: NFT,
-    pub currency: Currency,
-    pub against_currencies: Vec<Currency>,
+    pub basket: Basket,
+    pub against_currencies: Vec<CurrencyId>,
     pub expires_at: Timestamp, // when this expires, remove it
     pub expiry: u64,           // expiry of the wager in seconds
+    pub denom: Denom,
+    pub amount: Uint128,
+    pub against_amount: Uint128,
+    pub settlement_mode: SettlementMode,
+    pub direction: Direction,
+    pub wager_type: WagerType,
+    pub scoring_mode: ScoringMode,
+    pub depositor: Addr,
+    pub stake_type: StakeType,
+}
+
+#[cw_serde]
+pub struct Challenge {
+    pub opponent_token: Token,
+    pub currency: CurrencyId,
+    pub against_currency: CurrencyId,
+    pub denom: Denom,
     pub amount: Uint128,
+    pub expiry: u64, // expiry of the wager in seconds
+    // Address that paid the stake and is refunded when the challenge is cancelled
+    pub depositor: Addr,
+}
+
+#[cw_serde]
+pub struct ChallengeExport {
+    pub token: NFT,
+    pub opponent_token: NFT,
+    pub currency: CurrencyId,
+    pub against_currency: CurrencyId,
+    pub denom: Denom,
+    pub amount: Uint128,
+    pub expiry: u64, // expiry of the wager in seconds
+    pub depositor: Addr,
 }
 
 #[cw_serde]
 pub enum TokenStatus {
     Matchmaking(MatchmakingItemExport),
+    Challenge(ChallengeExport),
     Wager(WagerExport),
     None,
 }
 
-pub type Token = u64;
+// Collection address and token id
+pub type Token = (Addr, u64);
 pub type WagerKey = (Token, Token);
 
 pub struct WagerIndicies<'a> {
     pub id: UniqueIndex<'a, WagerKey, Wager>,
+    pub token: MultiIndex<'a, Token, Wager, WagerKey>,
 }
 
 impl<'a> IndexList<Wager> for WagerIndicies<'a> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Wager>> + '_> {
-        let v: Vec<&dyn Index<Wager>> = vec![&self.id];
+        let v: Vec<&dyn Index<Wager>> = vec![&self.id, &self.token];
         Box::new(v.into_iter())
     }
 }
 
+// Wagers by key, indexed by their second token so that a wager can be found from either token
 pub fn wagers<'a>() -> IndexedMap<'a, WagerKey, Wager, WagerIndicies<'a>> {
     let indexes = WagerIndicies {
-        id: UniqueIndex::new(|d| d.id, "wager_id"),
+        id: UniqueIndex::new(|d| d.id.clone(), "wager_id"),
+        token: MultiIndex::new(|_, d| d.id.1.clone(), "bids", "bids__token"),
     };
     IndexedMap::new("bids", indexes)
 }
 
-pub const MATCHMAKING: Map<Token, MatchmakingItem> = Map::new("matchmaking");
+pub struct ChallengeIndicies<'a> {
+    pub opponent: MultiIndex<'a, Token, Challenge, Token>,
+}
+
+impl<'a> IndexList<Challenge> for ChallengeIndicies<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Challenge>> + '_> {
+        let v: Vec<&dyn Index<Challenge>> = vec![&self.opponent];
+        Box::new(v.into_iter())
+    }
+}
+
+// Open challenges by challenger token, indexed by the challenged token
+pub fn challenges<'a>() -> IndexedMap<'a, Token, Challenge, ChallengeIndicies<'a>> {
+    let indexes = ChallengeIndicies {
+        opponent: MultiIndex::new(
+            |_, d| d.opponent_token.clone(),
+            "challenges",
+            "challenges__opponent",
+        ),
+    };
+    IndexedMap::new("challenges", indexes)
+}
+
+// Matchmaking items are bucketed by denom and amount, expiry and basket, and collection, so
+// that a wager only has to look at the items it could be matched with. Items are ordered by
+// when they expire within a bucket, so that expired items can be skipped without reading them
+pub type MatchmakingBucket = ((String, u128), (u64, String), Addr);
+
+pub struct MatchmakingIndicies<'a> {
+    pub bucket: MultiIndex<'a, (MatchmakingBucket, u64), MatchmakingItem, Token>,
+    pub expires_at: MultiIndex<'a, u64, MatchmakingItem, Token>,
+}
+
+impl<'a> IndexList<MatchmakingItem> for MatchmakingIndicies<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<MatchmakingItem>> + '_> {
+        let v: Vec<&dyn Index<MatchmakingItem>> = vec![&self.bucket, &self.expires_at];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn matchmaking<'a>() -> IndexedMap<'a, Token, MatchmakingItem, MatchmakingIndicies<'a>> {
+    let indexes = MatchmakingIndicies {
+        bucket: MultiIndex::new(
+            |pk, d| {
+                (
+                    (
+                        (denom_key(&d.denom), d.match_amounts().0),
+                        (d.expiry, basket_bucket(&d.basket)),
+                        Token::from_slice(pk)
+                            .expect("matchmaking keys are tokens")
+                            .0,
+                    ),
+                    d.expires_at.nanos(),
+                )
+            },
+            "matchmaking",
+            "matchmaking__bucket",
+        ),
+        expires_at: MultiIndex::new(
+            |_, d| d.expires_at.nanos(),
+            "matchmaking",
+            "matchmaking__expires_at",
+        ),
+    };
+    IndexedMap::new("matchmaking", indexes)
+}
+
+#[cw_serde]
+pub struct Price {
+    pub price: Decimal,
+    pub updated_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct Dispute {
+    pub disputer: Addr,
+    pub bond: Uint128,
+}
+
+#[cw_serde]
+pub struct ProposedResult {
+    // Winning token, `None` if the wager is a tie
+    pub winner: Option<Token>,
+    pub closing_prices: (Vec<Decimal>, Vec<Decimal>),
+    pub proposer: Addr,
+    pub dispute_ends_at: Timestamp,
+    pub dispute: Option<Dispute>,
+}
+
+pub const PROPOSED_RESULTS: Map<WagerKey, ProposedResult> = Map::new("proposed_results");
+
+// Tokens escrowed in the contract for a wager, with the address they are returned to
+pub const ESCROWED: Map<Token, Addr> = Map::new("escrowed");
+
+// STARS deposited to pay the stake of wagers made by sending an NFT to the contract
+pub const DEPOSITS: Map<&Addr, Uint128> = Map::new("deposits");
+
+// Price feed pushed by the admin, used when no oracles are configured
+pub const PRICES: Map<String, Price> = Map::new("prices");
+
+// Every price pushed to the feed by currency and time in seconds, used for TWAP settlement
+pub const PRICE_OBSERVATIONS: Map<(String, u64), Decimal> = Map::new("price_observations");
+
+// Prices recorded from each oracle by feed id, oracle and update time in nanoseconds, so that
+// wagers can still be settled on them once the oracles have moved on
+pub const ORACLE_OBSERVATIONS: Map<(String, &Addr, u64), Decimal> = Map::new("oracle_observations");
+
+#[cw_serde]
+pub enum PayoutPolicy {
+    // Pay the accounts that placed both sides of the wager
+    MatchOwner,
+    // Pay the owners of the tokens when the wager is settled
+    SettlementOwner,
+}
 
 #[cw_serde]
 pub struct Config {
     // Max amount of currencies that can be wagered against when matchmaking
     pub max_currencies: u8,
-    // List of wager amount options (ex: 50,100,250 STARS)
-    pub amounts: Vec<Uint128>,
     // List of wager expiry options in seconds (ex: 900,1800,3600)
     pub expiries: Vec<u64>,
+    // List of TWAP window options in seconds (ex: 300,600), empty disables TWAP settlement
+    pub twap_windows: Vec<u64>,
     // Percentage of the wager amount that goes to the fee collector
     pub fee_percent: Decimal,
     // Percentage of the wager amount that is fair burned
     pub fairburn_percent: Decimal,
     // Address that receives the fee
     pub fee_address: Addr,
-    // Address of the NFT collection
-    pub collection_address: Addr,
+    // Whether tokens of different collections can be matched or challenged
+    pub cross_collection: bool,
     // Time in seconds before a matchmaking item expires
     pub matchmaking_expiry: u64,
+    // Oracle contracts used to read prices, the median of their prices is used
+    pub oracles: Vec<Oracle>,
+    // Minimum amount of oracles that must return a fresh price
+    pub min_price_sources: u8,
+    // Time in seconds before a price is considered stale
+    pub max_price_age: u64,
+    // Keys trusted to sign price reports
+    pub signers: Vec<Signer>,
+    // Minimum amount of signers that must sign a price report
+    pub signature_threshold: u8,
+    // Time in seconds before the wager expiry within which a price report must be taken
+    pub attestation_window: u64,
+    // Percentage of the fee that goes to whoever settles the wager
+    pub keeper_reward_percent: Decimal,
+    // Percentage of each refunded stake that goes to whoever prunes expired matchmaking items
+    pub prune_reward_percent: Decimal,
+    // Time in seconds after a wager expires before it can be voided and refunded
+    pub settlement_grace_period: u64,
+    // Time in seconds a proposed result can be disputed, 0 pays out immediately
+    pub dispute_window: u64,
+    // Amount of STARS a participant must bond to dispute a result
+    pub dispute_bond: Uint128,
+    // Address that resolves disputes along with the admin
+    pub arbiter: Option<Addr>,
+    // Who receives winnings and refunds of a wager
+    pub payout_policy: PayoutPolicy,
+    // Flat amount of STARS paid by each side of a token wager when it is matched
+    pub token_wager_fee: Uint128,
+}
+
+#[cw_serde]
+pub enum OracleQueryFormat {
+    // {"price":{"currency":"atom"}}
+    Currency,
+    // {"price":{"symbol":"ATOM"}}
+    Symbol,
+}
+
+#[cw_serde]
+pub struct Oracle {
+    pub address: Addr,
+    pub query_format: OracleQueryFormat,
+}
+
+#[cw_serde]
+pub enum SignerKeyType {
+    Secp256k1,
+    Ed25519,
+}
+
+#[cw_serde]
+pub struct Signer {
+    pub key_type: SignerKeyType,
+    pub pubkey: Binary,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");